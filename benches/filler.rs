@@ -1,99 +1,66 @@
 use criterion::black_box;
-use std::sync::Arc;
 use xwords::{
     crossword::Crossword,
     fill::{filler::Filler, Fill},
     trie::Trie,
 };
 
-use criterion::{criterion_group, criterion_main, Benchmark, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
-    let group_id = "filler";
-
     let trie = Trie::load_default().expect("Failed to load trie");
 
-    let trie = Arc::new(trie);
-
-    let tmp_trie = trie.clone();
-
-    c.bench(
-        group_id,
-        Benchmark::new("empty_20201005_crossword", move |b| {
-            let mut filler = Filler::new(tmp_trie.as_ref());
-
-            let input = std::fs::read_to_string("./grids/20201012_empty.txt")
+    let mut group = c.benchmark_group("filler");
+
+    for name in [
+        "20201005_empty",
+        "20201012_empty",
+        "20201107_empty",
+        "20201128_empty",
+        "20201124_empty",
+    ] {
+        group.bench_function(format!("{}_crossword", name), |b| {
+            let mut filler = Filler::new(&trie, false, 120, false);
+            let input = std::fs::read_to_string(format!("./grids/{}.txt", name))
                 .expect("failed to read input");
             let input = Crossword::parse(input).expect("failed to parse input");
 
             b.iter(|| {
                 assert!(filler.fill(black_box(&input)).is_ok());
             });
-        }),
-    );
-
-    let tmp_trie = trie.clone();
-    c.bench(
-        group_id,
-        Benchmark::new("empty_20201012_crossword", move |b| {
-            let input = std::fs::read_to_string("./grids/20201012_empty.txt")
-                .expect("failed to read input");
-            let input = Crossword::parse(input).expect("failed to parse input");
-            let mut filler = Filler::new(tmp_trie.as_ref(), false);
-            b.iter(|| {
-                assert!(filler.fill(black_box(&input)).is_ok());
-            });
-        }),
-    );
-
-    let tmp_trie = trie.clone();
-
-    c.bench(
-        group_id,
-        Benchmark::new("empty_20201107_crossword", move |b| {
-            let mut filler = Filler::new(tmp_trie.as_ref(), false);
-            let input = std::fs::read_to_string("./grids/20201107_empty.txt")
-                .expect("failed to read input");
-            let input = Crossword::parse(input).expect("failed to parse input");
+        });
+    }
 
-            b.iter(|| {
-                assert!(filler.fill(black_box(&input)).is_ok());
-            });
-        }),
-    );
-
-    let tmp_trie = trie.clone();
-
-    c.bench(
-        group_id,
-        Benchmark::new("empty_20201128_crossword", move |b| {
-            let mut filler = Filler::new(tmp_trie.as_ref(), false);
-            let input = std::fs::read_to_string("./grids/20201128_empty.txt")
-                .expect("failed to read input");
-            let input = Crossword::parse(input).expect("failed to parse input");
+    group.finish();
+}
 
+pub fn parallel_benchmark(c: &mut Criterion) {
+    let trie = Trie::load_default().expect("Failed to load trie");
+    let input =
+        std::fs::read_to_string("./grids/20201012_empty.txt").expect("failed to read input");
+    let input = Crossword::parse(input).expect("failed to parse input");
+
+    let mut group = c.benchmark_group("parallel_filler");
+
+    // a new filler for every fill, since the threads of `fill_parallel` start with empty
+    // caches too
+    group.bench_function("single_thread", |b| {
+        b.iter(|| {
+            let mut filler = Filler::new(&trie, false, 120, false);
+            assert!(filler.fill(black_box(&input)).is_ok());
+        });
+    });
+    for num_threads in [2, 4, 8] {
+        group.bench_function(format!("{}_threads", num_threads), |b| {
             b.iter(|| {
-                assert!(filler.fill(black_box(&input)).is_ok());
+                let mut filler = Filler::new(&trie, false, 120, false);
+                assert!(filler.fill_parallel(black_box(&input), num_threads).is_ok());
             });
-        }),
-    );
+        });
+    }
 
-    let tmp_trie = trie.clone();
-
-    c.bench(
-        group_id,
-        Benchmark::new("empty_20201124_crossword", move |b| {
-            let mut filler = Filler::new(tmp_trie.as_ref(), false);
-            let input = std::fs::read_to_string("./grids/20201124_empty.txt")
-                .expect("failed to read input");
-            let input = Crossword::parse(input).expect("failed to parse input");
-
-            b.iter(|| {
-                assert!(filler.fill(black_box(&input)).is_ok());
-            });
-        }),
-    );
+    group.finish();
 }
 
-criterion_group!(benches, criterion_benchmark);
+criterion_group!(benches, criterion_benchmark, parallel_benchmark);
 criterion_main!(benches);
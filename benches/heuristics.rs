@@ -0,0 +1,43 @@
+use criterion::black_box;
+use xwords::{
+    crossword::Crossword,
+    fill::{filler::Filler, Fill, SlotHeuristic},
+    trie::Trie,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let trie = Trie::load_default().expect("Failed to load trie");
+
+    let mut group = c.benchmark_group("heuristics");
+    group.sample_size(10);
+
+    for name in [
+        "20201005_empty",
+        "20201012_empty",
+        "20201107_empty",
+        "20201128_empty",
+        "20201124_empty",
+    ] {
+        let input = std::fs::read_to_string(format!("./grids/{}.txt", name))
+            .expect("failed to read input");
+        let input = Crossword::parse(input).expect("failed to parse input");
+
+        for heuristic in [SlotHeuristic::FewestCandidates, SlotHeuristic::MinimumEntropy] {
+            group.bench_function(format!("{}_{:?}", name, heuristic), |b| {
+                let mut filler = Filler::new(&trie, false, 120, false);
+                filler.with_heuristic(heuristic);
+
+                b.iter(|| {
+                    assert!(filler.fill(black_box(&input)).is_ok());
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
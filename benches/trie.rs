@@ -1,39 +1,28 @@
 use criterion::black_box;
-use std::sync::Arc;
 use xwords::trie::Trie;
 
-use criterion::{criterion_group, criterion_main, Benchmark, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
-    let group_id = "trie";
-
     let trie = Trie::load_default().expect("Failed to load trie");
 
-    let trie = Arc::new(trie);
-
-    let tmp_trie = trie.clone();
+    let mut group = c.benchmark_group("trie");
 
-    c.bench(
-        group_id,
-        Benchmark::new("empty_word", move |b| {
-            b.iter(|| {
-                let input = "     ".chars();
-                assert!(tmp_trie.words(black_box(input)).len() > 0);
-            });
-        }),
-    );
+    group.bench_function("empty_word", |b| {
+        b.iter(|| {
+            let input = "     ".chars();
+            assert!(!trie.words(black_box(input)).is_empty());
+        });
+    });
 
-    let tmp_trie = trie.clone();
+    group.bench_function("partial_word", |b| {
+        b.iter(|| {
+            let input = " E R ".chars();
+            assert!(!trie.words(black_box(input)).is_empty());
+        });
+    });
 
-    c.bench(
-        group_id,
-        Benchmark::new("partial_word", move |b| {
-            b.iter(|| {
-                let input = " E R ".chars();
-                assert!(tmp_trie.words(black_box(input)).len() > 0);
-            });
-        }),
-    );
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
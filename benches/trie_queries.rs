@@ -0,0 +1,43 @@
+use criterion::black_box;
+use xwords::trie::Trie;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// Patterns of increasing wildcard density; a space is an open square.
+const PATTERNS: [(&str, [&str; 4]); 4] = [
+    ("no_wildcards", ["STARE", "OCEAN", "ALIBI", "ZZZZZ"]),
+    ("few_wildcards", ["ST RE", "OC AN", "A IBI", "Z ZZZ"]),
+    ("half_wildcards", [" T R ", "O E N", " L B ", "Q   Z"]),
+    ("all_wildcards", ["   ", "    ", "     ", "       "]),
+];
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let trie = Trie::load_default().expect("Failed to load trie");
+
+    let mut group = c.benchmark_group("trie_queries");
+
+    for (name, patterns) in PATTERNS.iter() {
+        group.throughput(Throughput::Elements(patterns.len() as u64));
+
+        group.bench_function(format!("is_viable_{}", name), |b| {
+            b.iter(|| {
+                for pattern in patterns.iter() {
+                    black_box(trie.is_viable(black_box(pattern.chars())));
+                }
+            });
+        });
+
+        group.bench_function(format!("words_{}", name), |b| {
+            b.iter(|| {
+                for pattern in patterns.iter() {
+                    black_box(trie.words(black_box(pattern.chars())));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
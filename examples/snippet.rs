@@ -21,7 +21,7 @@ XXXXX.XXXX.XXXX
 ",
 
     ))?;
-    let filled_crossword = fill_crossword_with_default_wordlist(&empty_crossword, false)?;
+    let filled_crossword = fill_crossword_with_default_wordlist(&empty_crossword, false, 120, false)?;
     println!("{}", filled_crossword);
     Ok(())
 }
@@ -1,6 +1,11 @@
 use crate::crossword::{Crossword, Direction};
+use crate::parse::{BLACK_SQUARE, VOID_SQUARE};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 
+/// Written in place of the clues missing from `AcrossFileFormat::with_clues`.
+const MISSING_CLUE: &str = "[NO CLUE]";
+
 /// Formats a Crossword into Across Puzzle V2 text file format.
 /// See https://www.litsoft.com/across/docs/AcrossTextFormat.pdf
 #[derive(PartialEq, Eq, Debug, Hash, Clone)]
@@ -9,6 +14,8 @@ pub struct AcrossFileFormat {
     pub(crate) title: String,
     pub(crate) author: String,
     pub(crate) copyright: String,
+    pub(crate) givens: Vec<(usize, usize)>,
+    pub(crate) clues: Option<BTreeMap<(Direction, u32), String>>,
 }
 
 impl AcrossFileFormat {
@@ -18,6 +25,138 @@ impl AcrossFileFormat {
             title,
             author,
             copyright,
+            givens: vec![],
+            clues: None,
+        }
+    }
+
+    /// The grid of the puzzle.
+    pub fn crossword(&self) -> &Crossword {
+        &self.crossword
+    }
+
+    /// Consumes the puzzle, returning its grid.
+    pub fn into_crossword(self) -> Crossword {
+        self.crossword
+    }
+
+    /// Marks the letters at the given `(row, col)` squares as given to the solver, as in a
+    /// starter puzzle. When some letters are given, the grid shows them marked, in lowercase,
+    /// and every other letter as `X`, to be solved.
+    pub fn with_givens(&mut self, givens: &[(usize, usize)]) -> &mut Self {
+        self.givens = givens.to_vec();
+        self
+    }
+
+    /// Sets the clues by direction and clue number, written in the `<ACROSS>` and `<DOWN>`
+    /// sections in clue order instead of the answers. Words without a clue get `[NO CLUE]`.
+    pub fn with_clues(&mut self, clues: HashMap<(Direction, u32), String>) -> &mut Self {
+        self.clues = Some(clues.into_iter().collect());
+        self
+    }
+
+    /// The lines of the `<ACROSS>` or `<DOWN>` section: the clues in clue order when there
+    /// are clues, the answers otherwise.
+    fn clue_lines(&self, direction: Direction) -> String {
+        let clues = match &self.clues {
+            Some(clues) => clues,
+            None => return self.crossword.words(direction).join("\n"),
+        };
+        self.crossword
+            .numbered_entries()
+            .iter()
+            .filter(|entry| entry.word_boundary.direction == direction)
+            .map(|entry| {
+                clues
+                    .get(&(direction.clone(), entry.number as u32))
+                    .map_or(MISSING_CLUE, String::as_str)
+            })
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+
+    /// The grid section, with the given letters marked when there are any.
+    fn grid(&self) -> String {
+        if self.givens.is_empty() {
+            return self.crossword.to_string();
+        }
+        let crossword = &self.crossword;
+        (0..crossword.height)
+            .map(|row| {
+                (0..crossword.width)
+                    .map(|col| {
+                        let c = crossword.contents[row * crossword.width + col];
+                        if BLACK_SQUARE.contains(&c) {
+                            c
+                        } else if c == VOID_SQUARE {
+                            ' '
+                        } else if c != ' ' && self.givens.contains(&(row, col)) {
+                            c.to_lowercase().next().unwrap_or(c)
+                        } else {
+                            'X'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Formats the words of the crossword as CSV, one `number,direction,row,col,length,answer`
+    /// row per word, across words first.
+    pub fn to_csv(&self) -> String {
+        self.crossword
+            .numbered_entries()
+            .iter()
+            .map(|entry| {
+                let direction = match entry.word_boundary.direction {
+                    Direction::Across => "across",
+                    Direction::Down => "down",
+                };
+                format!(
+                    "{},{},{},{},{},{}\n",
+                    entry.number,
+                    direction,
+                    entry.word_boundary.start_row,
+                    entry.word_boundary.start_col,
+                    entry.word_boundary.length,
+                    Self::csv_field(&entry.answer),
+                )
+            })
+            .collect()
+    }
+
+    /// Formats the answers as a Markdown draft for writing clues: an `Across` and a `Down`
+    /// section, each listing the answers of the direction as `- **1.** ANSWER` items in
+    /// clue order.
+    pub fn to_markdown(&self) -> String {
+        let entries = self.crossword.numbered_entries();
+        let section = |direction: Direction, title: &str| {
+            let items: String = entries
+                .iter()
+                .filter(|entry| entry.word_boundary.direction == direction)
+                .map(|entry| format!("- **{}.** {}\n", entry.number, entry.answer))
+                .collect();
+            format!("## {}\n\n{}", title, items)
+        };
+        format!(
+            "{}\n{}",
+            section(Direction::Across, "Across"),
+            section(Direction::Down, "Down")
+        )
+    }
+
+    /// Formats the crossword like `Display`, with `\r\n` line endings as expected by
+    /// legacy tools such as Across Lite.
+    pub fn to_string_crlf(&self) -> String {
+        self.to_string().lines().collect::<Vec<&str>>().join("\r\n")
+    }
+
+    fn csv_field(s: &str) -> String {
+        if s.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s.to_string()
         }
     }
 
@@ -45,7 +184,7 @@ impl fmt::Display for AcrossFileFormat {
 <SIZE>
 {}
 <GRID>
-{}
+{}{}
 <ACROSS>
 {}
 <DOWN>
@@ -54,15 +193,15 @@ impl fmt::Display for AcrossFileFormat {
             Self::indent(self.author.as_str(), indent_spaces),
             Self::indent(self.copyright.as_str(), indent_spaces),
             Self::indent(&format!("{}x{}", self.crossword.width, self.crossword.height), indent_spaces),
-            Self::indent(&format!("{}", self.crossword), indent_spaces),
-            Self::indent(
-                &self.crossword.words(Direction::Across).join("\n"),
-                indent_spaces
-            ),
-            Self::indent(
-                &self.crossword.words(Direction::Down).join("\n"),
-                indent_spaces
-            ),
+            Self::indent(&self.grid(), indent_spaces),
+            // lowercase letters of the grid are marked
+            if self.givens.is_empty() {
+                String::new()
+            } else {
+                format!("\n<REBUS>\n{}", Self::indent("MARK;", indent_spaces))
+            },
+            Self::indent(&self.clue_lines(Direction::Across), indent_spaces),
+            Self::indent(&self.clue_lines(Direction::Down), indent_spaces),
         )?;
         Ok(())
     }
@@ -70,7 +209,8 @@ impl fmt::Display for AcrossFileFormat {
 
 #[cfg(test)]
 mod tests {
-    use crate::crossword::Crossword;
+    use crate::crossword::{Crossword, Direction};
+    use std::collections::HashMap;
 
     #[test]
     fn format_works() {
@@ -112,4 +252,193 @@ RYAL
   MML"
         );
     }
+
+    #[test]
+    fn to_csv_works() {
+        let c = Crossword::parse(String::from(
+            "
+SIAM
+N.EM
+RYAL
+",
+        ))
+        .unwrap();
+        let word_count = c.words(Direction::Across).len() + c.words(Direction::Down).len();
+        let a = super::AcrossFileFormat::new(
+            c,
+            String::from("title"),
+            String::from("author"),
+            String::from("copyright"),
+        );
+
+        let csv = a.to_csv();
+
+        assert_eq!(word_count, csv.lines().count());
+        assert_eq!(
+            csv,
+            "1,across,0,0,4,SIAM
+4,across,1,2,2,EM
+5,across,2,0,4,RYAL
+1,down,0,0,3,SNR
+2,down,0,2,3,AEA
+3,down,0,3,3,MML
+"
+        );
+    }
+
+    #[test]
+    fn to_markdown_works() {
+        let c = Crossword::parse(String::from(
+            "
+SIAM
+N.EM
+RYAL
+",
+        ));
+        let a = super::AcrossFileFormat::new(
+            c.unwrap(),
+            String::from("title"),
+            String::from("author"),
+            String::from("copyright"),
+        );
+
+        let markdown = a.to_markdown();
+
+        let (across, down) = markdown.split_at(markdown.find("## Down").unwrap());
+        assert!(across.starts_with("## Across\n\n"));
+        let items = |section: &str| {
+            section
+                .lines()
+                .filter(|line| line.starts_with("- "))
+                .count()
+        };
+        assert_eq!(3, items(across));
+        assert_eq!(3, items(down));
+        assert!(across.contains("- **1.** SIAM\n- **4.** EM\n- **5.** RYAL\n"));
+        assert!(down.contains("- **1.** SNR\n- **2.** AEA\n- **3.** MML\n"));
+    }
+
+    #[test]
+    fn to_string_crlf_works() {
+        let c = Crossword::parse(String::from(
+            "
+SIAM
+N.EM
+RYAL
+",
+        ))
+        .unwrap();
+        let a = super::AcrossFileFormat::new(
+            c,
+            String::from("title"),
+            String::from("author"),
+            String::from("copyright"),
+        );
+
+        let crlf = a.to_string_crlf();
+
+        assert!(crlf.starts_with("<ACROSS PUZZLE V2>\r\n<TITLE>\r\n  title\r\n<AUTHOR>"));
+        assert!(crlf.contains("  RYAL\r\n<ACROSS>\r\n"));
+        assert!(crlf.contains("  RYAL\r\n<DOWN>\r\n"));
+        assert!(!crlf.replace("\r\n", "").contains('\n'));
+        assert_eq!(a.to_string(), crlf.replace("\r\n", "\n"));
+    }
+
+    #[test]
+    fn givens_are_marked() {
+        let c = Crossword::parse(String::from(
+            "
+SIAM
+N.EM
+RYAL
+",
+        ))
+        .unwrap();
+        let mut a = super::AcrossFileFormat::new(
+            c,
+            String::from("title"),
+            String::from("author"),
+            String::from("copyright"),
+        );
+        a.with_givens(&[(0, 0), (1, 3), (2, 2), (1, 1)]);
+
+        let text = a.to_string();
+
+        assert!(text.contains(
+            "<GRID>
+  sXXX
+  X.Xm
+  XXaX
+<REBUS>
+  MARK;
+<ACROSS>
+"
+        ));
+        assert!(!a.with_givens(&[]).to_string().contains("<REBUS>"));
+    }
+
+    #[test]
+    fn clues_follow_numbering() {
+        let c = Crossword::parse(String::from(
+            "
+.AB
+.CD
+EFG
+HIJ
+",
+        ))
+        .unwrap();
+        let mut a = super::AcrossFileFormat::new(
+            c,
+            String::from("title"),
+            String::from("author"),
+            String::from("copyright"),
+        );
+        let mut clues = HashMap::new();
+        clues.insert((Direction::Down, 4), String::from("Surprised cry"));
+        clues.insert((Direction::Down, 1), String::from("First down"));
+        clues.insert((Direction::Across, 5), String::from("Last across"));
+        a.with_clues(clues);
+
+        let text = a.to_string();
+
+        assert!(text.ends_with(
+            "<ACROSS>
+  [NO CLUE]
+  [NO CLUE]
+  [NO CLUE]
+  Last across
+<DOWN>
+  First down
+  [NO CLUE]
+  Surprised cry"
+        ));
+    }
+
+    #[test]
+    fn crossword_accessors_work() {
+        let c = Crossword::parse(String::from("AB\nCD")).unwrap();
+        let a = super::AcrossFileFormat::new(
+            c.clone(),
+            String::from("title"),
+            String::from("author"),
+            String::from("copyright"),
+        );
+
+        assert_eq!(&c, a.crossword());
+        assert_eq!(c, a.into_crossword());
+    }
+
+    #[test]
+    fn csv_field_quotes_commas() {
+        assert_eq!("ANSWER", super::AcrossFileFormat::csv_field("ANSWER"));
+        assert_eq!(
+            "\"HELLO, WORLD\"",
+            super::AcrossFileFormat::csv_field("HELLO, WORLD")
+        );
+        assert_eq!(
+            "\"SAY \"\"HI\"\"\"",
+            super::AcrossFileFormat::csv_field("SAY \"HI\"")
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use std::fs::File;
 use chrono::Datelike;
-use xwords::{fill::Fill, trie::Trie};
+use serde_json::json;
+use xwords::{crossword::Direction, fill::{Fill, TraceStep}, trie::Trie};
 
 use clap::{App, Arg};
 use xwords::{crossword::Crossword, fill::filler::Filler};
@@ -19,6 +20,7 @@ fn main() -> Result<(), String> {
         .arg(Arg::from_usage("[copyright] -c, --copyright <COPYRIGHT> 'Copyright text for across output. Defaults to `<YEAR> Public domain.`'"))
         .arg(Arg::from_usage("[log] -l, --log 'Prints intermediate progress information to stderr. Default is false.'"))
         .arg(Arg::from_usage("[profile] -p, --profile 'Profile the program. Default is false.'"))
+        .arg(Arg::from_usage("[trace] --trace <FILE> 'Writes the order in which the slots were filled to a JSON file when a solution is found.'"))
         .get_matches();
 
     let input_file_name = matches.value_of("input").expect("input not included");
@@ -48,10 +50,16 @@ fn main() -> Result<(), String> {
     let log = matches.is_present("log");
     
     let trie = Trie::load(words).expect("Failed to load trie");
-    let crossword = Filler::new(&trie, random, max_time_seconds, log).fill(&input);
+    let mut filler = Filler::new(&trie, random, max_time_seconds, log);
+    filler.with_trace(matches.is_present("trace"));
+    let crossword = filler.fill(&input);
 
     match crossword {
         Ok(crossword) => {
+            if let Some(trace_file_name) = matches.value_of("trace") {
+                write_trace(trace_file_name, filler.trace())?;
+            }
+
             let format = matches.value_of("format");
             let format = match format {
                 Some("across") => Format::Across,
@@ -80,11 +88,28 @@ fn main() -> Result<(), String> {
                 }
             }
         }
-        Err(s) => return Err(String::from(format!("Failed to fill crossword: {}", s))),
+        Err(s) => return Err(format!("Failed to fill crossword: {}", s)),
     }
     Ok(())
 }
 
+/// Writes the trace of a fill as a JSON array of `{"number", "direction", "word"}` objects,
+/// in the order the slots were filled.
+fn write_trace(file_name: &str, trace: &[TraceStep]) -> Result<(), String> {
+    let steps: Vec<serde_json::Value> = trace
+        .iter()
+        .map(|step| {
+            let direction = match step.direction {
+                Direction::Across => "across",
+                Direction::Down => "down",
+            };
+            json!({"number": step.number, "direction": direction, "word": step.word})
+        })
+        .collect();
+    let json = serde_json::to_string_pretty(&steps).map_err(|e| e.to_string())?;
+    std::fs::write(file_name, json)
+        .map_err(|e| format!("Failed to write trace to {}: {}", file_name, e))
+}
 
 /// The format of the output.
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
@@ -2,10 +2,33 @@
 Core types to represent a crossword puzzle.
 */
 
-use crate::parse::{parse_word_boundaries, WordBoundary};
-use std::{fmt, fs, hash::Hash};
+use crate::parse::{parse_word_boundaries, ParseOptions, WordBoundary, BLACK_SQUARE, VOID_SQUARE};
+use crate::symmetry::Symmetry;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt, fs,
+    hash::Hash,
+    str::Chars,
+};
 use std::path::Path;
 
+/// The shortest word allowed in a generated template.
+const TEMPLATE_MIN_WORD_LENGTH: usize = 3;
+
+/// The number of templates `Crossword::generate_template_with_word_count` tries before
+/// giving up.
+const TEMPLATE_MAX_ATTEMPTS: u64 = 100;
+
+/// The values of the English Scrabble tiles.
+pub const ENGLISH_TILE_VALUES: [(char, u32); 26] = [
+    ('A', 1), ('B', 3), ('C', 3), ('D', 2), ('E', 1), ('F', 4), ('G', 2), ('H', 4), ('I', 1),
+    ('J', 8), ('K', 5), ('L', 1), ('M', 3), ('N', 1), ('O', 1), ('P', 3), ('Q', 10), ('R', 1),
+    ('S', 1), ('T', 1), ('U', 1), ('V', 4), ('W', 4), ('X', 8), ('Y', 4), ('Z', 10),
+];
+
 /// The underlying representation of a crossword puzzle.
 /// All the contents are stored in a string, and the dimensions of the grid are stored explicitly.
 ///
@@ -14,11 +37,15 @@ use std::path::Path;
 /// for more information.
 /// In the contents, `.` or `:` represents a black square,
 /// and `X` represents a solution letter.
+///
+/// A rebus square holds several letters, kept in `rebus` by `(row, col)` while `contents`
+/// has the first of them. It is written as the letters in brackets, e.g. `[HEART]`.
 #[derive(PartialEq, Eq, Debug, Hash, Clone)]
 pub struct Crossword {
     pub(crate) contents: Vec<char>,
     pub(crate) width: usize,
     pub(crate) height: usize,
+    pub(crate) rebus: BTreeMap<(usize, usize), String>,
 }
 
 impl Crossword {
@@ -27,49 +54,627 @@ impl Crossword {
     pub fn parse_from_file<P>(file_path: P) -> Result<Crossword, String> where P: AsRef<Path>, {
         let name = file_path.as_ref().display().to_string();
         let contents = fs::read_to_string(file_path)
-            .expect(format!("Could not read file {}", name).as_str());
+            .unwrap_or_else(|_| panic!("Could not read file {}", name));
         Crossword::parse(contents)
     }
 
-    /// Parses a crossword from a string.
-    /// Err is returned if the contents cannot be parsed.
+    /// Parses a crossword from a string, with `X` for open squares, `.` or `:` for black
+    /// squares, spaces for the void squares of non-rectangular puzzles and the letters of
+    /// rebus squares in brackets. Letters are stored in uppercase, to match the word lists.
+    /// Err is returned if the contents cannot be parsed, e.g. if rows have different lengths.
     pub fn parse(contents: String) -> Result<Crossword, String> {
-        let grid: Vec<Vec<char>> = contents
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| line.chars().collect())
-            .collect();
+        Crossword::parse_with_options(contents, ParseOptions::default())
+    }
+
+    /// Parses a crossword like `Crossword::parse`, with the black square and open square
+    /// characters of `options`, e.g. `#` for black squares as exported by some tools. Black
+    /// squares other than `.` and `:` are stored as `.`, so they display as in
+    /// `Crossword::parse`. Err is returned if the open square character is also a black
+    /// square character, or if the contents cannot be parsed.
+    pub fn parse_with_options(
+        contents: String,
+        options: ParseOptions,
+    ) -> Result<Crossword, String> {
+        if options.block_chars.contains(options.unfilled) {
+            return Err(format!(
+                "`{}` cannot be both an open and a black square",
+                options.unfilled.escape_default()
+            ));
+        }
+        let mut rebus = BTreeMap::new();
+        let mut grid: Vec<Vec<char>> = vec![];
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let mut row = vec![];
+            let mut chars = line.chars();
+            while let Some(c) = chars.next() {
+                if c != '[' {
+                    row.push(c);
+                    continue;
+                }
+                let mut letters = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        closed = true;
+                        break;
+                    }
+                    letters.extend(c.to_uppercase());
+                }
+                if letters.is_empty() || !closed {
+                    return Err(format!("Invalid rebus square in row: {}", line));
+                }
+                rebus.insert((grid.len(), row.len()), letters);
+                row.push('[');
+            }
+            grid.push(row);
+        }
         
         // Validate grid dimensions
         let height = grid.len();
         if height == 0 {
             return Err("Empty grid".to_string());
         }
-        let width = grid[0].len();
+        // the longest row sets the width, so a short first row is the one reported,
+        // e.g. when the spaces of its leading void squares were left out
+        let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
         if width == 0 {
             return Err("Empty row in grid".to_string());
         }
         
         // Ensure all rows have same width
-        if grid.iter().any(|row| row.len() != width) {
-            return Err("Inconsistent row lengths".to_string());
+        if let Some((index, row)) = grid.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(format!(
+                "Row {} has {} squares instead of {}, write void squares as spaces",
+                index,
+                row.len(),
+                width
+            ));
         }
         
-        let contents = Crossword::clean(&contents);
+        let mut contents = Crossword::clean(grid, &options);
+        for ((row, col), letters) in rebus.iter() {
+            contents[row * width + col] = letters.chars().next().unwrap();
+        }
+        let crossword = Crossword {
+            contents,
+            width,
+            height,
+            rebus,
+        };
+        debug_assert!(crossword.validate_dimensions().is_ok());
+        Ok(crossword)
+    }
+
+    /// Checks that the grid holds exactly `width * height` squares, as indexing by
+    /// `row * width + col` throughout the crate assumes.
+    pub fn validate_dimensions(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!("Empty {}x{} grid", self.width, self.height));
+        }
+        if self.contents.len() != self.width * self.height {
+            return Err(format!(
+                "Grid of {}x{} has {} squares instead of {}",
+                self.width,
+                self.height,
+                self.contents.len(),
+                self.width * self.height
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses a solved crossword from a string where black squares are spaces and every
+    /// other character is a solution letter, as exported by some tools.
+    ///
+    /// Spaces are converted to `.` black squares, so they don't clash with the space used
+    /// internally for open squares, and `X` is kept as a letter. Rows shorter than the
+    /// longest row are padded with black squares, since trailing spaces are often stripped.
+    /// Empty lines are ignored. Err is returned if the contents are empty.
+    pub fn parse_space_blocks(contents: &str) -> Result<Crossword, String> {
+        let grid: Vec<Vec<char>> = contents
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.chars().map(|c| if c == ' ' { '.' } else { c }).collect())
+            .collect();
+
+        let height = grid.len();
+        if height == 0 {
+            return Err("Empty grid".to_string());
+        }
+        let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+
+        let contents = grid
+            .into_iter()
+            .flat_map(|mut row| {
+                row.resize(width, '.');
+                row
+            })
+            .collect();
         Ok(Crossword {
             contents,
             width,
             height,
+            rebus: BTreeMap::new(),
         })
     }
 
-    fn clean(contents: &String) -> Vec<char> {
-        contents.chars()
-            .filter(|c| *c != '\n')
-            .map(|c| if c == 'X' { ' ' } else { c })  // internally use space for blank squares
+    /// Parses a crossword like `Crossword::parse` from a string where squares may be
+    /// separated by `separator` for readability, so `X|X|.` parses like `XX.`. Every
+    /// `separator` is removed, including ones before the first or after the last square
+    /// of a row. Err is returned if `separator` could be a square itself, i.e. it is a
+    /// letter, a digit or a black square, or it is a line break.
+    pub fn parse_with_separator(contents: &str, separator: char) -> Result<Crossword, String> {
+        if separator.is_alphanumeric()
+            || BLACK_SQUARE.contains(&separator)
+            || separator == '\n'
+            || separator == '\r'
+        {
+            return Err(format!("`{}` cannot separate squares", separator.escape_default()));
+        }
+        Crossword::parse(contents.chars().filter(|c| *c != separator).collect())
+    }
+
+    /// Returns the `height` x `width` part of the grid starting at `start_row`, `start_col`.
+    /// Err is returned if the region is empty or does not fit in the grid.
+    ///
+    /// Cropping can break the symmetry of the grid, see `Crossword::symmetry`.
+    pub fn crop(
+        &self,
+        start_row: usize,
+        start_col: usize,
+        height: usize,
+        width: usize,
+    ) -> Result<Crossword, String> {
+        if width == 0 || height == 0 {
+            return Err("Empty crop region".to_string());
+        }
+        if start_row + height > self.height || start_col + width > self.width {
+            return Err(format!(
+                "Crop region {}x{} at ({}, {}) does not fit in {}x{} grid",
+                width, height, start_row, start_col, self.width, self.height
+            ));
+        }
+
+        let contents = (start_row..start_row + height)
+            .flat_map(|row| {
+                let start = row * self.width + start_col;
+                self.contents[start..start + width].iter().copied()
+            })
+            .collect();
+        let rebus = self
+            .rebus
+            .range((start_row, 0)..(start_row + height, 0))
+            .filter(|((_, col), _)| (start_col..start_col + width).contains(col))
+            .map(|((row, col), letters)| ((row - start_row, col - start_col), letters.clone()))
+            .collect();
+        Ok(Crossword {
+            contents,
+            width,
+            height,
+            rebus,
+        })
+    }
+
+    /// Returns the grid surrounded by the given number of rows and columns of black squares.
+    ///
+    /// Padding unevenly can break the symmetry of the grid, see `Crossword::symmetry`.
+    pub fn pad(&self, top: usize, right: usize, bottom: usize, left: usize) -> Crossword {
+        let width = left + self.width + right;
+        let height = top + self.height + bottom;
+        let mut contents = vec!['.'; width * height];
+        for row in 0..self.height {
+            let start = (top + row) * width + left;
+            contents[start..start + self.width]
+                .copy_from_slice(&self.contents[row * self.width..(row + 1) * self.width]);
+        }
+        let rebus = self
+            .rebus
+            .iter()
+            .map(|((row, col), letters)| ((row + top, col + left), letters.clone()))
+            .collect();
+        Crossword {
+            contents,
+            width,
+            height,
+            rebus,
+        }
+    }
+
+    /// Returns the grid with rows and columns swapped, so that across words become down
+    /// words and down words across words, e.g. to read a grid entered column by column.
+    pub fn transpose(&self) -> Crossword {
+        let contents = (0..self.width)
+            .flat_map(|col| (0..self.height).map(move |row| (row, col)))
+            .map(|(row, col)| self.contents[row * self.width + col])
+            .collect();
+        let rebus = self
+            .rebus
+            .iter()
+            .map(|((row, col), letters)| ((*col, *row), letters.clone()))
+            .collect();
+        Crossword {
+            contents,
+            width: self.height,
+            height: self.width,
+            rebus,
+        }
+    }
+
+    /// Returns the grid with every letter removed, keeping the black squares, such as the
+    /// empty grid a solver starts from.
+    pub fn clear_letters(&self) -> Crossword {
+        let contents = self
+            .contents
+            .iter()
+            .map(|c| if BLACK_SQUARE.contains(c) || *c == VOID_SQUARE { *c } else { ' ' })
+            .collect();
+        Crossword {
+            contents,
+            width: self.width,
+            height: self.height,
+            rebus: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the square at `row`, `col` to `c`, using the characters of `Crossword::parse`:
+    /// `X` clears the square, a space makes it void and letters are uppercased. Returns the
+    /// edit made, which can be undone with `Crossword::revert_edit`. Err is returned if the
+    /// square is outside the grid.
+    pub fn set_char(&mut self, row: usize, col: usize, c: char) -> Result<GridEdit, String> {
+        let old = self.char_at(row, col)?;
+        let c = match c {
+            'X' => c,
+            c => uppercase(c),
+        };
+        let edit = GridEdit { row, col, old, new: c };
+        self.rebus.remove(&(row, col));
+        self.contents[row * self.width + col] = match c {
+            'X' => ' ',
+            ' ' => VOID_SQUARE,
+            c => c,
+        };
+        Ok(edit)
+    }
+
+    /// Sets the square at `row`, `col` to `c` like `Crossword::set_char`, without keeping
+    /// the edit. Err is returned if the square is outside the grid or the grid doesn't
+    /// hold `width * height` squares, in which case the grid is unchanged.
+    pub fn set_cell(&mut self, row: usize, col: usize, c: char) -> Result<(), String> {
+        self.validate_dimensions()?;
+        self.set_char(row, col, c).map(|_| ())
+    }
+
+    /// The character at `row`, `col`, as in `Crossword::parse` with `X` for an open square,
+    /// or None if the square is outside the grid.
+    pub fn get_cell(&self, row: usize, col: usize) -> Option<char> {
+        if row * self.width + col >= self.contents.len() {
+            return None;
+        }
+        self.char_at(row, col).ok()
+    }
+
+    /// Makes the square at `row`, `col` a rebus square holding `letters` in uppercase, which
+    /// words through the square then read in full, see `WordIterator`. A single letter
+    /// makes a regular square, and `X` is the letter rather than an open square. Err is
+    /// returned if the square is outside the grid, is black or void, or if `letters` is
+    /// empty, in which case the grid is unchanged.
+    pub fn set_rebus(&mut self, row: usize, col: usize, letters: &str) -> Result<(), String> {
+        let current = self.char_at(row, col)?;
+        if BLACK_SQUARE.contains(&current) || current == ' ' {
+            return Err(format!("Square ({}, {}) cannot hold letters", row, col));
+        }
+        let letters = letters.to_uppercase();
+        let first = letters
+            .chars()
+            .next()
+            .ok_or_else(|| "Empty rebus".to_string())?;
+        self.rebus.remove(&(row, col));
+        self.contents[row * self.width + col] = first;
+        if letters.chars().count() > 1 {
+            self.rebus.insert((row, col), letters);
+        }
+        Ok(())
+    }
+
+    /// The letters of the rebus square at `row`, `col`, or None if it is not a rebus square.
+    pub fn rebus(&self, row: usize, col: usize) -> Option<&str> {
+        self.rebus.get(&(row, col)).map(String::as_str)
+    }
+
+    /// Applies `edit`, typically to redo it. Err is returned if the square is outside the
+    /// grid or does not currently hold `edit.old`, in which case the grid is unchanged.
+    pub fn apply_edit(&mut self, edit: &GridEdit) -> Result<(), String> {
+        self.replace_char(edit.row, edit.col, edit.old, edit.new)
+    }
+
+    /// Undoes `edit`. Err is returned if the square is outside the grid or does not
+    /// currently hold `edit.new`, in which case the grid is unchanged.
+    pub fn revert_edit(&mut self, edit: &GridEdit) -> Result<(), String> {
+        self.replace_char(edit.row, edit.col, edit.new, edit.old)
+    }
+
+    fn replace_char(&mut self, row: usize, col: usize, from: char, to: char) -> Result<(), String> {
+        let current = self.char_at(row, col)?;
+        if current != from {
+            return Err(format!(
+                "Expected `{}` at ({}, {}) but found `{}`",
+                from, row, col, current
+            ));
+        }
+        self.set_char(row, col, to).map(|_| ())
+    }
+
+    /// The character at `row`, `col`, with open squares as `X` and void squares as spaces.
+    fn char_at(&self, row: usize, col: usize) -> Result<char, String> {
+        if row >= self.height || col >= self.width {
+            return Err(format!(
+                "Square ({}, {}) is outside the {}x{} grid",
+                row, col, self.width, self.height
+            ));
+        }
+        Ok(match self.contents[row * self.width + col] {
+            ' ' => 'X',
+            VOID_SQUARE => ' ',
+            c => c,
+        })
+    }
+
+    /// Generates an empty template of the given size with 180 degree rotational symmetry
+    /// and about `block_ratio` of the squares black. The same `seed` always produces
+    /// the same template.
+    ///
+    /// Blocks are only placed where every word stays at least three letters long and
+    /// all open squares stay connected, so fewer blocks than requested may be placed.
+    /// Err is returned if the grid is smaller than 3x3.
+    pub fn generate_template(
+        width: usize,
+        height: usize,
+        block_ratio: f64,
+        seed: u64,
+    ) -> Result<Crossword, String> {
+        if width < TEMPLATE_MIN_WORD_LENGTH || height < TEMPLATE_MIN_WORD_LENGTH {
+            return Err(format!(
+                "Template must be at least {0}x{0}, got {1}x{2}",
+                TEMPLATE_MIN_WORD_LENGTH, width, height
+            ));
+        }
+
+        let size = width * height;
+        let target_blocks = (block_ratio.clamp(0.0, 1.0) * size as f64).round() as usize;
+        let mut template = Crossword {
+            contents: vec![' '; size],
+            width,
+            height,
+            rebus: BTreeMap::new(),
+        };
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut indices: Vec<usize> = (0..size).collect();
+        indices.shuffle(&mut rng);
+
+        let mut blocks = 0;
+        for index in indices {
+            if blocks >= target_blocks {
+                break;
+            }
+            let partner = size - 1 - index;
+            if template.contents[index] == '.' {
+                continue;
+            }
+            template.contents[index] = '.';
+            template.contents[partner] = '.';
+            if template.is_valid_template() {
+                blocks += if index == partner { 1 } else { 2 };
+            } else {
+                template.contents[index] = ' ';
+                template.contents[partner] = ' ';
+            }
+        }
+
+        Ok(template)
+    }
+
+    /// Generates a template like `generate_template` with between `min_words` and
+    /// `max_words` words, both included, to avoid filling too sparse or too dense grids.
+    /// Templates are generated from `seed` and the following seeds until one has a word
+    /// count in range. Err is returned if the grid is smaller than 3x3 or none of the
+    /// attempted templates has a word count in range.
+    pub fn generate_template_with_word_count(
+        width: usize,
+        height: usize,
+        block_ratio: f64,
+        seed: u64,
+        min_words: usize,
+        max_words: usize,
+    ) -> Result<Crossword, String> {
+        for attempt in 0..TEMPLATE_MAX_ATTEMPTS {
+            let seed = seed.wrapping_add(attempt);
+            let template = Crossword::generate_template(width, height, block_ratio, seed)?;
+            let word_count = parse_word_boundaries(&template).len();
+            if (min_words..=max_words).contains(&word_count) {
+                return Ok(template);
+            }
+        }
+        Err(format!(
+            "None of {} generated templates has between {} and {} words",
+            TEMPLATE_MAX_ATTEMPTS, min_words, max_words
+        ))
+    }
+
+    /// Enumerates empty templates of the given size with exactly `blacks` black squares
+    /// placed with the given symmetry, returning at most `limit` of them.
+    ///
+    /// Like `generate_template`, only templates where every word is at least three
+    /// letters long and all open squares are connected are returned. Without a `seed`
+    /// templates come in a fixed order; with a seed the search order is shuffled, which
+    /// gives a reproducible random sample.
+    ///
+    /// The number of templates grows combinatorially with the grid size, and when few
+    /// valid templates exist the search can take very long, so keep `limit` and the grid
+    /// small.
+    pub fn enumerate_templates(
+        width: usize,
+        height: usize,
+        blacks: usize,
+        symmetry: Symmetry,
+        limit: usize,
+        seed: Option<u64>,
+    ) -> Vec<Crossword> {
+        let mut template = Crossword {
+            contents: vec![' '; width * height],
+            width,
+            height,
+            rebus: BTreeMap::new(),
+        };
+
+        // squares that must be black together for the template to keep its symmetry
+        let mut orbits: Vec<Vec<usize>> = (0..width * height)
+            .filter_map(|index| {
+                let (row, col) = (index / width, index % width);
+                let (partner_row, partner_col) = symmetry.partner(&template, row, col);
+                let partner = partner_row * width + partner_col;
+                match index.cmp(&partner) {
+                    Ordering::Less => Some(vec![index, partner]),
+                    Ordering::Equal => Some(vec![index]),
+                    Ordering::Greater => None,
+                }
+            })
+            .collect();
+        if let Some(seed) = seed {
+            orbits.shuffle(&mut StdRng::seed_from_u64(seed));
+        }
+
+        let mut result = vec![];
+        if limit > 0 {
+            template.enumerate_blacks(&orbits, 0, blacks, limit, &mut result);
+        }
+        result
+    }
+
+    fn enumerate_blacks(
+        &mut self,
+        orbits: &[Vec<usize>],
+        start: usize,
+        blacks: usize,
+        limit: usize,
+        result: &mut Vec<Crossword>,
+    ) {
+        if blacks == 0 {
+            if self.is_valid_template() {
+                result.push(self.clone());
+            }
+            return;
+        }
+
+        for (index, orbit) in orbits.iter().enumerate().skip(start) {
+            if orbit.len() > blacks {
+                continue;
+            }
+            for square in orbit {
+                self.contents[*square] = '.';
+            }
+            // more black squares can never reconnect the grid
+            if self.is_connected() {
+                self.enumerate_blacks(orbits, index + 1, blacks - orbit.len(), limit, result);
+            }
+            for square in orbit {
+                self.contents[*square] = ' ';
+            }
+            if result.len() >= limit {
+                return;
+            }
+        }
+    }
+
+    /// Checks that every run of open squares is a word of at least
+    /// `TEMPLATE_MIN_WORD_LENGTH` letters, and that all open squares are connected.
+    fn is_valid_template(&self) -> bool {
+        let is_open = |row: usize, col: usize| self.contents[row * self.width + col] != '.';
+
+        let rows = (0..self.height)
+            .map(|row| (0..self.width).map(|col| is_open(row, col)).collect());
+        let cols = (0..self.width)
+            .map(|col| (0..self.height).map(|row| is_open(row, col)).collect());
+        let has_short_run = rows.chain(cols).any(|line: Vec<bool>| {
+            line.split(|open| !open)
+                .any(|run| !run.is_empty() && run.len() < TEMPLATE_MIN_WORD_LENGTH)
+        });
+        !has_short_run && self.is_connected()
+    }
+
+    /// Checks that all open squares are connected, and that there is at least one.
+    fn is_connected(&self) -> bool {
+        let open_count = self.contents.iter().filter(|c| **c != '.').count();
+        let start = match self.contents.iter().position(|c| *c != '.') {
+            Some(start) => start,
+            None => return false,
+        };
+        let mut visited = vec![false; self.contents.len()];
+        visited[start] = true;
+        let mut queue = VecDeque::from(vec![start]);
+        let mut reached = 0;
+        while let Some(index) = queue.pop_front() {
+            reached += 1;
+            let (row, col) = (index / self.width, index % self.width);
+            let mut neighbors = Vec::with_capacity(4);
+            if row > 0 {
+                neighbors.push(index - self.width);
+            }
+            if row + 1 < self.height {
+                neighbors.push(index + self.width);
+            }
+            if col > 0 {
+                neighbors.push(index - 1);
+            }
+            if col + 1 < self.width {
+                neighbors.push(index + 1);
+            }
+            for neighbor in neighbors {
+                if !visited[neighbor] && self.contents[neighbor] != '.' {
+                    visited[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        reached == open_count
+    }
+
+    fn clean(grid: Vec<Vec<char>>, options: &ParseOptions) -> Vec<char> {
+        grid.into_iter()
+            .flatten()
+            // internally use space for blank squares
+            .map(|c| match c {
+                c if c == options.unfilled => ' ',
+                ' ' => VOID_SQUARE,
+                c if c != VOID_SQUARE
+                    && !BLACK_SQUARE.contains(&c)
+                    && options.block_chars.contains(c) =>
+                {
+                    '.'
+                }
+                // the word lists are in uppercase, so given letters must be too
+                c => uppercase(c),
+            })
             .collect()
     }
 
+    /// Returns the total English Scrabble value of the letters in the grid. Flashy fills
+    /// with rare letters score higher. See `Crossword::scrabble_score_with` for other
+    /// languages.
+    pub fn scrabble_score(&self) -> u32 {
+        self.scrabble_score_with(&ENGLISH_TILE_VALUES.iter().copied().collect())
+    }
+
+    /// Returns the total value of the letters in the grid, as given by `tile_values`.
+    /// Letters are looked up in uppercase and letters without a value count as 0.
+    pub fn scrabble_score_with(&self, tile_values: &HashMap<char, u32>) -> u32 {
+        self.contents
+            .iter()
+            .flat_map(|c| c.to_uppercase())
+            .filter_map(|c| tile_values.get(&c))
+            .sum()
+    }
+
     /// Returns all words with at least two letters
     /// in the crossword for a given direction as a Vec of strings
     pub fn words(&self, direction: Direction) -> Vec<String> {
@@ -84,16 +689,272 @@ impl Crossword {
             .filter(|word: &String| word.len() >= 2)
             .collect()
     }
+
+    /// Classifies the grid by its dimensions, or returns `None` if it is not a common size.
+    pub fn standard_size(&self) -> Option<StandardSize> {
+        match (self.width, self.height) {
+            (5, 5) => Some(StandardSize::Mini),
+            (15, 15) => Some(StandardSize::Daily),
+            (21, 21) => Some(StandardSize::Sunday),
+            _ => None,
+        }
+    }
+
+    /// Counts the words of each length in the given direction.
+    pub fn length_histogram(&self, direction: Direction) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for word_boundary in parse_word_boundaries(self)
+            .iter()
+            .filter(|wb| wb.direction == direction)
+        {
+            *histogram.entry(word_boundary.length).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Returns all words in the crossword with their clue numbers, across words first,
+    /// each direction ordered by clue number.
+    ///
+    /// Clue numbers are assigned in reading order to every square that starts a word.
+    pub fn numbered_entries(&self) -> Vec<NumberedEntry> {
+        let word_boundaries = parse_word_boundaries(self);
+        let starts = Crossword::word_starts(&word_boundaries);
+
+        let mut result: Vec<NumberedEntry> = word_boundaries
+            .iter()
+            .map(|wb| NumberedEntry {
+                number: starts.binary_search(&(wb.start_row, wb.start_col)).unwrap() + 1,
+                answer: pattern_string(self, wb, ' '),
+                word_boundary: wb.clone(),
+            })
+            .collect();
+        result.sort_by_key(|entry| {
+            (
+                entry.word_boundary.direction == Direction::Down,
+                entry.number,
+            )
+        });
+        result
+    }
+
+    /// Returns the clue number, direction and boundary of every word in the crossword equal
+    /// to `word`, ignoring case, across words first.
+    pub fn find_word(&self, word: &str) -> Vec<(usize, Direction, WordBoundary)> {
+        let word = word.to_uppercase();
+        self.numbered_entries()
+            .into_iter()
+            .filter(|entry| entry.answer.to_uppercase() == word)
+            .map(|entry| {
+                (
+                    entry.number,
+                    entry.word_boundary.direction.clone(),
+                    entry.word_boundary,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the across and down words covering the square at `row`, `col`, across
+    /// first. Empty for a black square or a square outside the grid, and a square covered
+    /// by a single word in one direction has just that word.
+    pub fn slots_at(&self, row: usize, col: usize) -> Vec<(Direction, WordBoundary)> {
+        if row >= self.height || col >= self.width || self.is_black(row, col) {
+            return vec![];
+        }
+        let mut slots: Vec<(Direction, WordBoundary)> = parse_word_boundaries(self)
+            .into_iter()
+            .filter(|wb| wb.squares().any(|square| square == (row, col)))
+            .map(|wb| (wb.direction.clone(), wb))
+            .collect();
+        slots.sort_by_key(|(direction, _)| *direction == Direction::Down);
+        slots
+    }
+
+    /// Checks the grid for squares the filler can't fill as a regular crossword, in reading
+    /// order: squares in no word, and unchecked squares, in a word in one direction only and
+    /// so a one-letter word in the other. Each square is reported once. Useful as a
+    /// pre-flight check before `Filler::fill`.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut covered = vec![(false, false); self.contents.len()];
+        for wb in parse_word_boundaries(self) {
+            for (row, col) in wb.squares() {
+                let square = &mut covered[row * self.width + col];
+                match wb.direction {
+                    Direction::Across => square.0 = true,
+                    Direction::Down => square.1 = true,
+                }
+            }
+        }
+
+        let mut errors = vec![];
+        for (index, c) in self.contents.iter().enumerate() {
+            if BLACK_SQUARE.contains(c) || *c == VOID_SQUARE {
+                continue;
+            }
+            let (row, col) = (index / self.width, index % self.width);
+            let missing = match covered[index] {
+                (true, true) => continue,
+                (false, false) => {
+                    errors.push(ValidationError::IsolatedSquare { row, col });
+                    continue;
+                }
+                (true, false) => Direction::Down,
+                (false, true) => Direction::Across,
+            };
+            errors.push(ValidationError::OneLetterWord {
+                row,
+                col,
+                direction: missing,
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the clue number of every square that starts a word of two letters or more,
+    /// in reading order. Numbers start from 1 and follow reading order, as in
+    /// `Crossword::numbered_entries`.
+    pub fn number_cells(&self) -> Vec<CellNumber> {
+        Crossword::word_starts(&parse_word_boundaries(self))
+            .into_iter()
+            .enumerate()
+            .map(|(index, (row, col))| CellNumber {
+                row,
+                col,
+                number: index + 1,
+            })
+            .collect()
+    }
+
+    /// Iterates all squares in reading order as `(row, col, number, char)`, where `number`
+    /// is the clue number of squares that start a word and `char` is the current content
+    /// of the square (a space for an open square).
+    pub fn cells_with_numbers(
+        &self,
+    ) -> impl Iterator<Item = (usize, usize, Option<usize>, char)> + '_ {
+        let starts = Crossword::word_starts(&parse_word_boundaries(self));
+        let mut next_start = 0;
+        self.contents.iter().enumerate().map(move |(index, c)| {
+            let (row, col) = (index / self.width, index % self.width);
+            let number = if starts.get(next_start) == Some(&(row, col)) {
+                next_start += 1;
+                Some(next_start)
+            } else {
+                None
+            };
+            (row, col, number, *c)
+        })
+    }
+
+    /// Returns the squares that start a word, in reading order. The clue number of a
+    /// square is its index in the result plus one.
+    fn word_starts(word_boundaries: &[WordBoundary]) -> Vec<(usize, usize)> {
+        let mut starts: Vec<(usize, usize)> = word_boundaries
+            .iter()
+            .map(|wb| (wb.start_row, wb.start_col))
+            .collect();
+        starts.sort_unstable();
+        starts.dedup();
+        starts
+    }
+}
+
+/// A change of a single square of a `Crossword`, as returned by `Crossword::set_char`.
+/// Characters are as in `Crossword::parse`, with `X` for an open square. Editors can keep
+/// a stack of edits and undo or redo them with `Crossword::revert_edit` and
+/// `Crossword::apply_edit`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GridEdit {
+    pub row: usize,
+    pub col: usize,
+    pub old: char,
+    pub new: char,
+}
+
+/// The clue number of a square, see `Crossword::number_cells`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct CellNumber {
+    pub row: usize,
+    pub col: usize,
+    pub number: usize,
+}
+
+/// A word of a `Crossword` together with its clue number.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NumberedEntry {
+    pub number: usize,
+    pub word_boundary: WordBoundary,
+    pub answer: String,
+}
+
+/// A problem with a square of a `Crossword`, as returned by `Crossword::validate`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum ValidationError {
+    /// A letter square with no letter squares next to it, so it is in no word.
+    IsolatedSquare { row: usize, col: usize },
+    /// A letter square alone between black squares or edges in `direction`, so it is
+    /// unchecked: in a word in the other direction only.
+    OneLetterWord {
+        row: usize,
+        col: usize,
+        direction: Direction,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::IsolatedSquare { row, col } => {
+                write!(f, "Square ({}, {}) is isolated", row, col)
+            }
+            ValidationError::OneLetterWord {
+                row,
+                col,
+                direction,
+            } => write!(
+                f,
+                "Square ({}, {}) is a one-letter {:?} word",
+                row, col, direction
+            ),
+        }
+    }
+}
+
+/// Returns the current contents of the word at `word_boundary` in `crossword`, with open
+/// squares rendered as `wildcard`.
+pub fn pattern_string(
+    crossword: &Crossword,
+    word_boundary: &WordBoundary,
+    wildcard: char,
+) -> String {
+    WordIterator::new(crossword, word_boundary)
+        .map(|c| if c == ' ' { wildcard } else { c })
+        .collect()
+}
+
+/// The uppercase of `c`, or `c` itself if its uppercase is several characters.
+fn uppercase(c: char) -> char {
+    let mut upper = c.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(upper), None) => upper,
+        _ => c,
+    }
 }
 
 /// An `Iterator<char>` that correctly traversing a Crossword, accounting for direction.
 ///
-/// The length of the word is stored in the `word_boundary`.
+/// The length of the word is stored in the `word_boundary`. Rebus squares yield all their
+/// letters, so a word through them has more letters than its length.
 #[derive(Clone, Debug)]
 pub struct WordIterator<'s> {
     crossword: &'s Crossword,
     pub word_boundary: &'s WordBoundary,
     index: usize,
+    rebus: Option<Chars<'s>>,
 }
 
 impl<'s> WordIterator<'s> {
@@ -102,6 +963,7 @@ impl<'s> WordIterator<'s> {
             crossword,
             word_boundary,
             index: 0,
+            rebus: None,
         }
     }
 }
@@ -119,27 +981,33 @@ impl<'s> Iterator for WordIterator<'s> {
     type Item = char;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(c) = self.rebus.as_mut().and_then(|letters| letters.next()) {
+            return Some(c);
+        }
         if self.index >= self.word_boundary.length {
             return None;
         }
 
-        match self.word_boundary.direction {
-            Direction::Across => {
-                let char_index = self.word_boundary.start_row * self.crossword.width
-                    + self.word_boundary.start_col
-                    + self.index;
-                let result = self.crossword.contents[char_index];
-                self.index += 1;
-                Some(result)
-            }
-            Direction::Down => {
-                let char_index = (self.word_boundary.start_row + self.index) * self.crossword.width
-                    + self.word_boundary.start_col;
-                let result = self.crossword.contents[char_index];
-                self.index += 1;
-                Some(result)
+        let (row, col) = match self.word_boundary.direction {
+            Direction::Across => (
+                self.word_boundary.start_row,
+                self.word_boundary.start_col + self.index,
+            ),
+            Direction::Down => (
+                self.word_boundary.start_row + self.index,
+                self.word_boundary.start_col,
+            ),
+        };
+        self.index += 1;
+        if !self.crossword.rebus.is_empty() {
+            if let Some(letters) = self.crossword.rebus.get(&(row, col)) {
+                let mut letters = letters.chars();
+                let result = letters.next();
+                self.rebus = Some(letters);
+                return result;
             }
         }
+        Some(self.crossword.contents[row * self.crossword.width + col])
     }
 }
 
@@ -167,10 +1035,18 @@ impl fmt::Display for Crossword {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for row in 0..self.height {
             for col in 0..self.width {
+                if let Some(letters) = self.rebus.get(&(row, col)) {
+                    write!(f, "[{}]", letters)?;
+                    continue;
+                }
                 let char = self.contents[row * self.width + col];
                 // for unsolved cells, put back standard across file format X
                 // for an omitted solution letter instead of space which is used internally
-                let char = if char == ' ' { 'X' } else { char };
+                let char = match char {
+                    ' ' => 'X',
+                    VOID_SQUARE => ' ',
+                    c => c,
+                };
                 write!(f, "{}", char)?;
             }
             if row < self.height - 1 {
@@ -181,8 +1057,19 @@ impl fmt::Display for Crossword {
     }
 }
 
+/// A common grid size, see `Crossword::standard_size`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum StandardSize {
+    /// A 5x5 mini puzzle.
+    Mini,
+    /// A 15x15 daily puzzle.
+    Daily,
+    /// A 21x21 Sunday puzzle.
+    Sunday,
+}
+
 /// The direction of a word in a Crossword.
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize, Deserialize)]
 pub enum Direction {
     Across,
     Down,
@@ -190,11 +1077,17 @@ pub enum Direction {
 
 #[cfg(test)]
 mod tests {
-    use super::Crossword;
-    use crate::{crossword::WordIterator, parse::WordBoundary};
-    use std::collections::HashSet;
+    use super::{
+        pattern_string, CellNumber, Crossword, GridEdit, StandardSize, ValidationError,
+    };
+    use crate::{
+        crossword::WordIterator,
+        parse::{parse_word_boundaries, BlockChars, ParseOptions, WordBoundary},
+    };
+    use std::collections::{BTreeMap, HashMap, HashSet};
 
     use super::Direction;
+    use crate::symmetry::Symmetry;
 
     #[test]
 
@@ -210,12 +1103,39 @@ ghi
         assert!(result.is_ok());
 
         let c = result.unwrap();
-        assert_eq!(String::from("abcdefghi"), c.contents.iter().collect::<String>());
+        assert_eq!(String::from("ABCDEFGHI"), c.contents.iter().collect::<String>());
         assert_eq!(3, c.width);
         assert_eq!(3, c.height);
         println!("{}", c);
     }
 
+    #[test]
+    fn parse_ignores_carriage_returns() {
+        let c = Crossword::parse(String::from("AB.\r\nXYZ\r\n")).unwrap();
+
+        assert_eq!(Ok(()), c.validate_dimensions());
+        assert_eq!(String::from("AB. YZ"), c.contents.iter().collect::<String>());
+    }
+
+    #[test]
+    fn validate_dimensions_detects_corruption() {
+        let corrupted = Crossword {
+            contents: "ABC\rDEF".chars().collect(),
+            width: 3,
+            height: 2,
+            rebus: BTreeMap::new(),
+        };
+        assert!(corrupted.validate_dimensions().is_err());
+
+        let empty = Crossword {
+            contents: vec![],
+            width: 0,
+            height: 2,
+            rebus: BTreeMap::new(),
+        };
+        assert!(empty.validate_dimensions().is_err());
+    }
+
     #[test]
     fn crossword_iterator_works() {
         let input = Crossword::parse(String::from("
@@ -234,6 +1154,7 @@ GHI
             crossword: &input,
             word_boundary: &word_boundary,
             index: 0,
+            rebus: None,
         };
 
         let s: String = t.collect();
@@ -251,6 +1172,7 @@ GHI
             crossword: &input,
             word_boundary: &word_boundary,
             index: 0,
+            rebus: None,
         };
 
         let s: String = t.collect();
@@ -258,6 +1180,26 @@ GHI
         assert_eq!(String::from("ADG"), s);
     }
 
+    #[test]
+    fn pattern_string_works() {
+        let input = Crossword::parse(String::from("
+ABC
+DXF
+GHX
+")).unwrap();
+        let word_boundary = WordBoundary::new(0, 1, 3, Direction::Down);
+
+        let manual: String = WordIterator::new(&input, &word_boundary).collect();
+
+        assert_eq!(manual, pattern_string(&input, &word_boundary, ' '));
+        assert_eq!("B H", manual);
+        assert_eq!("B?H", pattern_string(&input, &word_boundary, '?'));
+        assert_eq!(
+            "GH_",
+            pattern_string(&input, &WordBoundary::new(2, 0, 3, Direction::Across), '_')
+        );
+    }
+
     #[test]
     fn crossword_iterator_eq_works() {
         let input = Crossword::parse(String::from("
@@ -282,12 +1224,14 @@ CXX
             crossword: &input,
             word_boundary: &a,
             index: 0,
+            rebus: None,
         };
 
         let b_iter = WordIterator {
             crossword: &input,
             word_boundary: &b,
             index: 0,
+            rebus: None,
         };
 
         assert_eq!(a_iter, b_iter);
@@ -317,12 +1261,14 @@ CXX
             crossword: &input,
             word_boundary: &a,
             index: 0,
+            rebus: None,
         };
 
         let b_iter = WordIterator {
             crossword: &input,
             word_boundary: &b,
             index: 0,
+            rebus: None,
         };
 
         let mut set = HashSet::new();
@@ -346,4 +1292,544 @@ RYAL
         assert_eq!(vec!["SIAM", "EM", "RYAL"], across_words);
         assert_eq!(vec!["SNR", "AEA", "MML"], down_words);
     }
+
+    #[test]
+    fn parse_space_blocks_works() {
+        let input = Crossword::parse_space_blocks("
+SIAM
+N EM
+RYAX
+FOX
+").unwrap();
+
+        assert_eq!(4, input.width);
+        assert_eq!(4, input.height);
+        assert_eq!(
+            "SIAMN.EMRYAXFOX.",
+            input.contents.iter().collect::<String>()
+        );
+        assert_eq!(vec!["SNRF", "YO", "AEAX", "MMX"], input.words(Direction::Down));
+        assert!(Crossword::parse_space_blocks("\n\n").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_jagged_rows() {
+        assert_eq!(
+            Err(String::from(
+                "Row 1 has 2 squares instead of 3, write void squares as spaces"
+            )),
+            Crossword::parse(String::from("XXX\nXX\nXXX"))
+        );
+        assert_eq!(
+            Err(String::from(
+                "Row 0 has 2 squares instead of 3, write void squares as spaces"
+            )),
+            Crossword::parse(String::from("XX\nXXX\nXXX"))
+        );
+        assert!(Crossword::parse(String::from(" XX\nXXX\nXXX")).is_ok());
+    }
+
+    #[test]
+    fn parse_with_options_works() {
+        let options = ParseOptions {
+            block_chars: BlockChars::new(&['#']),
+            unfilled: '_',
+        };
+
+        let c = Crossword::parse_with_options(String::from("_X#\nAB_"), options.clone()).unwrap();
+
+        assert_eq!(" X.AB ", c.contents.iter().collect::<String>());
+        assert_eq!(
+            vec![
+                WordBoundary::new(0, 0, 2, Direction::Across),
+                WordBoundary::new(1, 0, 3, Direction::Across),
+                WordBoundary::new(0, 0, 2, Direction::Down),
+                WordBoundary::new(0, 1, 2, Direction::Down),
+            ],
+            parse_word_boundaries(&c)
+        );
+        let conflicting = ParseOptions {
+            unfilled: '#',
+            ..options
+        };
+        assert!(Crossword::parse_with_options(String::from("##"), conflicting).is_err());
+    }
+
+    #[test]
+    fn parse_with_separator_works() {
+        let expected = Crossword::parse(String::from("
+XX.
+ABX
+")).unwrap();
+
+        let piped = Crossword::parse_with_separator("
+X|X|.
+A|B|X
+", '|').unwrap();
+        assert_eq!(expected, piped);
+
+        let framed = Crossword::parse_with_separator("|X|X|.|\n|A|B|X|", '|').unwrap();
+        assert_eq!(expected, framed);
+
+        let spaced = Crossword::parse_with_separator("X X .\nA B X", ' ').unwrap();
+        assert_eq!(expected, spaced);
+
+        assert!(Crossword::parse_with_separator("X.X.", '.').is_err());
+        assert!(Crossword::parse_with_separator("XAXA", 'A').is_err());
+        assert!(Crossword::parse_with_separator("X|X\nX", '|').is_err());
+    }
+
+    #[test]
+    fn slots_at_works() {
+        let input = Crossword::parse(String::from("
+SIAM
+N.EM
+RYAL
+")).unwrap();
+
+        let slots = input.slots_at(2, 2);
+        assert_eq!(
+            vec![
+                (Direction::Across, WordBoundary::new(2, 0, 4, Direction::Across)),
+                (Direction::Down, WordBoundary::new(0, 2, 3, Direction::Down)),
+            ],
+            slots
+        );
+        assert_eq!(
+            vec![(Direction::Down, WordBoundary::new(0, 0, 3, Direction::Down))],
+            input.slots_at(1, 0)
+        );
+        assert!(input.slots_at(1, 1).is_empty());
+        assert!(input.slots_at(3, 0).is_empty());
+    }
+
+    #[test]
+    fn crop_works() {
+        let input = Crossword::parse(String::from("
+SIAM
+N.EM
+RYAL
+")).unwrap();
+
+        let cropped = input.crop(1, 1, 2, 3).unwrap();
+        assert_eq!(3, cropped.width);
+        assert_eq!(2, cropped.height);
+        assert_eq!(".EMYAL", cropped.contents.iter().collect::<String>());
+
+        assert!(input.crop(1, 1, 3, 3).is_err());
+        assert!(input.crop(0, 2, 1, 3).is_err());
+        assert!(input.crop(0, 0, 0, 3).is_err());
+    }
+
+    #[test]
+    fn pad_works() {
+        let input = Crossword::parse(String::from("
+SI
+NX
+")).unwrap();
+
+        let padded = input.pad(1, 0, 0, 2);
+        assert_eq!(4, padded.width);
+        assert_eq!(3, padded.height);
+        assert_eq!("......SI..N ", padded.contents.iter().collect::<String>());
+    }
+
+    #[test]
+    fn transpose_works() {
+        let input = Crossword::parse(String::from(
+            "
+SIAM
+N.EM
+RYAL
+",
+        ))
+        .unwrap();
+
+        let transposed = input.transpose();
+        assert_eq!(3, transposed.width);
+        assert_eq!(4, transposed.height);
+        assert_eq!(
+            "SNRI.YAEAMML",
+            transposed.contents.iter().collect::<String>()
+        );
+        assert_eq!(
+            input.words(Direction::Across),
+            transposed.words(Direction::Down)
+        );
+        assert_eq!(
+            input.words(Direction::Down),
+            transposed.words(Direction::Across)
+        );
+        assert_eq!(input, transposed.transpose());
+    }
+
+    #[test]
+    fn clear_letters_works() {
+        let input = Crossword::parse(String::from("
+SIX
+N:M
+")).unwrap();
+
+        let cleared = input.clear_letters();
+        assert_eq!(3, cleared.width);
+        assert_eq!(2, cleared.height);
+        assert_eq!("XXX\nX:X", cleared.to_string());
+    }
+
+    #[test]
+    fn apply_and_revert_edits_works() {
+        let original = Crossword::parse(String::from("
+AXC
+X.X
+")).unwrap();
+        let mut grid = original.clone();
+
+        let edits = vec![
+            grid.set_char(0, 1, 'B').unwrap(),
+            grid.set_char(0, 0, 'X').unwrap(),
+            grid.set_char(1, 2, 'D').unwrap(),
+            grid.set_char(1, 2, 'E').unwrap(),
+        ];
+        assert_eq!(GridEdit { row: 0, col: 1, old: 'X', new: 'B' }, edits[0]);
+        assert_eq!("XBC\nX.E", grid.to_string());
+        let edited = grid.clone();
+
+        for edit in edits.iter().rev() {
+            grid.revert_edit(edit).unwrap();
+        }
+        assert_eq!(original, grid);
+
+        for edit in &edits {
+            grid.apply_edit(edit).unwrap();
+        }
+        assert_eq!(edited, grid);
+
+        assert!(grid.revert_edit(&edits[0]).is_ok());
+        assert!(grid.revert_edit(&edits[0]).is_err());
+        assert!(grid.set_char(2, 0, 'A').is_err());
+    }
+
+    #[test]
+    fn scrabble_score_works() {
+        let input = Crossword::parse(String::from("
+QUIZ
+A.X.
+XBBc
+")).unwrap();
+
+        // Q U I Z A B B C = 10 + 1 + 1 + 10 + 1 + 3 + 3 + 3, the X squares are open
+        assert_eq!(32, input.scrabble_score());
+
+        let tile_values: HashMap<char, u32> = vec![('Ă', 7), ('A', 1)].into_iter().collect();
+        let input = Crossword::parse(String::from("
+ĂA
+X.
+")).unwrap();
+        assert_eq!(8, input.scrabble_score_with(&tile_values));
+    }
+
+    #[test]
+    fn void_squares_work() {
+        let mut grid = Crossword::parse(String::from(" AB \nCDEF\n GH ")).unwrap();
+
+        assert_eq!(" AB \nCDEF\n GH ", grid.to_string());
+        assert_eq!(" XX \nXXXX\n XX ", grid.clear_letters().to_string());
+        assert_eq!(Some(' '), grid.get_cell(0, 0));
+        assert_eq!(5, parse_word_boundaries(&grid).len());
+
+        grid.set_cell(1, 0, ' ').unwrap();
+        assert_eq!(" AB \n DEF\n GH ", grid.to_string());
+        assert_eq!(5, parse_word_boundaries(&grid).len());
+    }
+
+    #[test]
+    fn set_cell_works() {
+        let mut grid = Crossword::parse(String::from(
+            "
+ABC
+DEF
+",
+        ))
+        .unwrap();
+
+        grid.set_cell(1, 2, 'Z').unwrap();
+        grid.set_cell(0, 0, 'X').unwrap();
+        grid.set_cell(0, 1, '.').unwrap();
+
+        assert_eq!(Some('Z'), grid.get_cell(1, 2));
+        assert_eq!(Some('X'), grid.get_cell(0, 0));
+        assert_eq!(Some('.'), grid.get_cell(0, 1));
+        assert_eq!(None, grid.get_cell(2, 0));
+        assert_eq!(None, grid.get_cell(0, 3));
+        assert_eq!("X.C\nDEZ", grid.to_string());
+
+        // letters are uppercased as in `Crossword::parse`
+        grid.set_cell(1, 1, 'q').unwrap();
+        assert_eq!(Some('Q'), grid.get_cell(1, 1));
+        assert_eq!(Crossword::parse(String::from("X.C\nDqZ")).unwrap(), grid);
+        let edit = grid.set_char(1, 0, 'é').unwrap();
+        assert_eq!('É', edit.new);
+        grid.revert_edit(&edit).unwrap();
+        assert_eq!("X.C\nDQZ", grid.to_string());
+
+        assert!(grid.set_cell(2, 0, 'A').is_err());
+        assert!(grid.set_cell(0, 3, 'A').is_err());
+        let mut invalid = Crossword {
+            contents: vec!['A', 'B', 'C'],
+            width: 2,
+            height: 2,
+            rebus: BTreeMap::new(),
+        };
+        assert!(invalid.set_cell(0, 0, 'Z').is_err());
+        assert_eq!(vec!['A', 'B', 'C'], invalid.contents);
+        assert_eq!(None, invalid.get_cell(1, 1));
+    }
+
+    #[test]
+    fn generate_template_works() {
+        let template = Crossword::generate_template(7, 7, 0.2, 42).unwrap();
+
+        assert_eq!(7, template.width);
+        assert_eq!(7, template.height);
+        assert!(template.contents.contains(&'.'));
+        for index in 0..template.contents.len() {
+            assert_eq!(
+                template.contents[index] == '.',
+                template.contents[template.contents.len() - 1 - index] == '.'
+            );
+        }
+        assert!(template.is_valid_template());
+        assert_eq!(template, Crossword::generate_template(7, 7, 0.2, 42).unwrap());
+        assert!(Crossword::generate_template(2, 7, 0.2, 42).is_err());
+    }
+
+    #[test]
+    fn generate_template_with_word_count_works() {
+        for seed in 0..5 {
+            let template =
+                Crossword::generate_template_with_word_count(9, 9, 0.2, seed, 30, 32).unwrap();
+            let word_count = parse_word_boundaries(&template).len();
+            assert!((30..=32).contains(&word_count), "{} words", word_count);
+            assert!(template.is_valid_template());
+        }
+        assert!(Crossword::generate_template_with_word_count(9, 9, 0.2, 0, 100, 120).is_err());
+    }
+
+    #[test]
+    fn number_cells_works() {
+        let input = Crossword::parse(String::from("
+SIAM
+N.EM
+RYAL
+")).unwrap();
+
+        let cell = |row, col, number| CellNumber { row, col, number };
+        assert_eq!(
+            vec![cell(0, 0, 1), cell(0, 2, 2), cell(0, 3, 3), cell(1, 2, 4), cell(2, 0, 5)],
+            input.number_cells()
+        );
+
+        let daily = Crossword::parse_from_file("grids/20201005_empty.txt").unwrap();
+        let numbers = daily.number_cells();
+        assert_eq!(1, numbers[0].number);
+        assert_eq!(numbers.len(), numbers.last().unwrap().number);
+        for entry in daily.numbered_entries() {
+            let start = (entry.word_boundary.start_row, entry.word_boundary.start_col);
+            assert!(numbers
+                .iter()
+                .any(|n| (n.row, n.col) == start && n.number == entry.number));
+        }
+        assert_eq!(numbers, daily.number_cells());
+    }
+
+    #[test]
+    fn cells_with_numbers_works() {
+        let input = Crossword::parse(String::from("
+SIAM
+N.EM
+RYAL
+")).unwrap();
+
+        let cells: Vec<(usize, usize, Option<usize>, char)> =
+            input.cells_with_numbers().collect();
+
+        assert_eq!(12, cells.len());
+        assert_eq!((0, 0, Some(1), 'S'), cells[0]);
+        assert_eq!((0, 1, None, 'I'), cells[1]);
+        assert_eq!((1, 1, None, '.'), cells[5]);
+        assert_eq!((2, 3, None, 'L'), cells[11]);
+
+        let word_starts: HashSet<(usize, usize)> = parse_word_boundaries(&input)
+            .iter()
+            .map(|wb| (wb.start_row, wb.start_col))
+            .collect();
+        let numbered: HashSet<(usize, usize)> = cells
+            .iter()
+            .filter(|(_, _, number, _)| number.is_some())
+            .map(|(row, col, _, _)| (*row, *col))
+            .collect();
+        assert_eq!(word_starts, numbered);
+
+        let numbers: Vec<usize> = cells.iter().filter_map(|(_, _, number, _)| *number).collect();
+        assert_eq!(vec![1, 2, 3, 4, 5], numbers);
+    }
+
+    #[test]
+    fn enumerate_templates_works() {
+        let templates = Crossword::enumerate_templates(5, 5, 4, Symmetry::Rotational, 3, None);
+
+        assert_eq!(3, templates.len());
+        for template in templates.iter() {
+            assert_eq!(4, template.contents.iter().filter(|c| **c == '.').count());
+            assert!(template.has_symmetry(Symmetry::Rotational));
+            assert!(template.is_connected());
+            assert!(template.is_valid_template());
+        }
+        let distinct: HashSet<&Crossword> = templates.iter().collect();
+        assert_eq!(3, distinct.len());
+
+        let sample = Crossword::enumerate_templates(5, 5, 4, Symmetry::Rotational, 3, Some(7));
+        assert_eq!(3, sample.len());
+        assert_eq!(
+            sample,
+            Crossword::enumerate_templates(5, 5, 4, Symmetry::Rotational, 3, Some(7))
+        );
+
+        let odd = Crossword::enumerate_templates(7, 7, 1, Symmetry::Rotational, 10, None);
+        assert_eq!(1, odd.len());
+        assert_eq!('.', odd[0].contents[24]);
+        assert!(Crossword::enumerate_templates(5, 5, 1, Symmetry::Rotational, 10, None).is_empty());
+    }
+
+    #[test]
+    fn standard_size_works() {
+        let daily = Crossword::parse_from_file("grids/20201005_empty.txt").unwrap();
+        assert_eq!(Some(StandardSize::Daily), daily.standard_size());
+
+        let seven = Crossword::generate_template(7, 7, 0.2, 1).unwrap();
+        assert_eq!(None, seven.standard_size());
+
+        assert_eq!(Some(StandardSize::Sunday), seven.pad(7, 7, 7, 7).standard_size());
+        assert_eq!(None, seven.pad(0, 8, 0, 0).standard_size());
+    }
+
+    #[test]
+    fn length_histogram_works() {
+        let input = Crossword::parse_from_file("grids/20201005_empty.txt").unwrap();
+
+        let across = input.length_histogram(Direction::Across);
+        let down = input.length_histogram(Direction::Down);
+
+        assert_eq!(
+            vec![(3, 10), (4, 9), (5, 5), (6, 7), (10, 3), (14, 2)],
+            across.clone().into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            across.values().sum::<usize>() + down.values().sum::<usize>(),
+            parse_word_boundaries(&input).len()
+        );
+        assert!(down.keys().all(|length| *length >= 2));
+    }
+
+    #[test]
+    fn find_word_works() {
+        let input = Crossword::parse(String::from("
+ABC
+B.X
+CXX
+")).unwrap();
+
+        assert_eq!(
+            vec![
+                (1, Direction::Across, WordBoundary::new(0, 0, 3, Direction::Across)),
+                (1, Direction::Down, WordBoundary::new(0, 0, 3, Direction::Down)),
+            ],
+            input.find_word("abc")
+        );
+        assert!(input.find_word("ABX").is_empty());
+        assert!(input.find_word("AB").is_empty());
+    }
+
+    #[test]
+    fn numbered_entries_works() {
+        let input = Crossword::parse(String::from("
+SIAM
+N.EM
+RYAL
+")).unwrap();
+
+        let entries: Vec<(usize, Direction, String)> = input
+            .numbered_entries()
+            .into_iter()
+            .map(|entry| (entry.number, entry.word_boundary.direction, entry.answer))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (1, Direction::Across, String::from("SIAM")),
+                (4, Direction::Across, String::from("EM")),
+                (5, Direction::Across, String::from("RYAL")),
+                (1, Direction::Down, String::from("SNR")),
+                (2, Direction::Down, String::from("AEA")),
+                (3, Direction::Down, String::from("MML")),
+            ],
+            entries
+        );
+    }
+
+    #[test]
+    fn validate_works() {
+        let valid = Crossword::parse(String::from("XX.\nXXX\n.XX")).unwrap();
+        assert_eq!(Ok(()), valid.validate());
+
+        let invalid = Crossword::parse(String::from("XXX\nX..\nX.X")).unwrap();
+        let one_letter = |row, col, direction| ValidationError::OneLetterWord {
+            row,
+            col,
+            direction,
+        };
+        assert_eq!(
+            Err(vec![
+                one_letter(0, 1, Direction::Down),
+                one_letter(0, 2, Direction::Down),
+                one_letter(1, 0, Direction::Across),
+                one_letter(2, 0, Direction::Across),
+                ValidationError::IsolatedSquare { row: 2, col: 2 },
+            ]),
+            invalid.validate()
+        );
+    }
+
+    #[test]
+    fn rebus_works() {
+        let c = Crossword::parse(String::from("S[TAR]T\nAB.")).unwrap();
+
+        assert_eq!(3, c.width);
+        assert_eq!(Some("TAR"), c.rebus(0, 1));
+        assert_eq!(vec!["START", "AB"], c.words(Direction::Across));
+        assert_eq!(vec!["SA", "TARB"], c.words(Direction::Down));
+        assert_eq!("S[TAR]T\nAB.", c.to_string());
+        assert!(Crossword::parse(String::from("S[TART\nAB.")).is_err());
+        assert!(Crossword::parse(String::from("S[]T\nAB.")).is_err());
+
+        let mut grid = Crossword::parse(String::from("ABC\nDE.")).unwrap();
+        grid.set_rebus(0, 1, "TAR").unwrap();
+        assert_eq!(vec!["ATARC", "DE"], grid.words(Direction::Across));
+        assert_eq!("A[TAR]C\nDE.", grid.to_string());
+        assert!(grid.set_rebus(1, 2, "AB").is_err());
+        assert!(grid.set_rebus(0, 0, "").is_err());
+
+        grid.set_char(0, 1, 'E').unwrap();
+        assert_eq!(None, grid.rebus(0, 1));
+        assert_eq!("AEC\nDE.", grid.to_string());
+
+        // letters are uppercased, and a leading X is a letter rather than an open square
+        grid.set_rebus(0, 1, "xr").unwrap();
+        assert_eq!(Some("XR"), grid.rebus(0, 1));
+        assert_eq!('X', grid.contents[1]);
+        assert_eq!(Crossword::parse(String::from("A[xr]C\nDE.")).unwrap(), grid);
+        grid.set_rebus(0, 1, "X").unwrap();
+        assert_eq!(None, grid.rebus(0, 1));
+        assert_eq!(vec!["AXC", "DE"], grid.words(Direction::Across));
+    }
 }
@@ -0,0 +1,111 @@
+/*!
+A rough label of how hard a grid is to fill, for users who don't want to read
+fill statistics.
+
+The label combines three measures, each worth 0 to 2 points:
+
+- open squares: 0 points up to 16, 1 point up to 64, 2 points above;
+- average number of candidate words of the unfilled slots: 0 points from 1000,
+  1 point from 50, 2 points below;
+- candidate words of the tightest unfilled slot: 0 points from 20, 1 point from 3,
+  2 points below.
+
+A grid with at most 1 point is `Easy`, with 2 points `Medium`, and `Hard` otherwise.
+*/
+
+use crate::{
+    crossword::{Crossword, WordIterator},
+    parse::parse_word_boundaries,
+    trie::Trie,
+};
+
+/// How hard a grid is to fill, see `Crossword::difficulty_label`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Crossword {
+    /// Labels how hard the grid is to fill with the words of `trie`. See the module
+    /// documentation for the thresholds. A grid without unfilled slots is `Easy`.
+    pub fn difficulty_label(&self, trie: &Trie) -> Difficulty {
+        let open_squares = self.contents.iter().filter(|c| **c == ' ').count();
+        let candidate_counts: Vec<usize> = parse_word_boundaries(self)
+            .iter()
+            .map(|word_boundary| WordIterator::new(self, word_boundary))
+            .filter(|iter| iter.clone().any(|c| c == ' '))
+            .map(|iter| trie.words(iter).len())
+            .collect();
+        if candidate_counts.is_empty() {
+            return Difficulty::Easy;
+        }
+        let average = candidate_counts.iter().sum::<usize>() / candidate_counts.len();
+        let tightest = *candidate_counts.iter().min().unwrap();
+
+        let points = points_above(open_squares, &[16, 64])
+            + points_below(average, &[1000, 50])
+            + points_below(tightest, &[20, 3]);
+        match points {
+            0..=1 => Difficulty::Easy,
+            2 => Difficulty::Medium,
+            _ => Difficulty::Hard,
+        }
+    }
+}
+
+/// One point for each of the increasing `thresholds` that `value` is above.
+fn points_above(value: usize, thresholds: &[usize]) -> usize {
+    thresholds.iter().filter(|threshold| value > **threshold).count()
+}
+
+/// One point for each of the decreasing `thresholds` that `value` is below.
+fn points_below(value: usize, thresholds: &[usize]) -> usize {
+    thresholds.iter().filter(|threshold| value < **threshold).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{crossword::Crossword, trie::Trie};
+
+    use super::Difficulty;
+
+    #[test]
+    fn difficulty_label_works() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+
+        let open = Crossword::parse(String::from(
+            "
+XXXXX
+XXXXX
+XXXXX
+XXXXX
+XXXXX
+",
+        ))
+        .unwrap();
+        assert_eq!(Difficulty::Easy, open.difficulty_label(&trie));
+
+        let constrained = Crossword::parse(String::from(
+            "
+QXXXZ
+XXXXX
+XXJXX
+XXXXX
+ZXXXQ
+",
+        ))
+        .unwrap();
+        assert_eq!(Difficulty::Hard, constrained.difficulty_label(&trie));
+
+        let filled = Crossword::parse(String::from(
+            "
+AB
+CD
+",
+        ))
+        .unwrap();
+        assert_eq!(Difficulty::Easy, filled.difficulty_label(&trie));
+    }
+}
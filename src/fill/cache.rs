@@ -5,22 +5,16 @@ to prevent repeated work.
 
 use std::hash::{Hash, Hasher};
 
-use rustc_hash::{FxHashMap, FxHasher};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
 
-use crate::trie::Trie;
+use crate::{fill::min_letter_entropy, trie::Trie};
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct CachedWords {
     words_cache: FxHashMap<u64, Vec<String>>,
 }
 
 impl CachedWords {
-    pub fn default() -> CachedWords {
-        CachedWords {
-            words_cache: FxHashMap::default(),
-        }
-    }
-
     pub fn words<T: Iterator<Item = char> + Clone>(
         &mut self,
         iter: T,
@@ -38,18 +32,12 @@ impl CachedWords {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct CachedIsViable {
     is_viable_cache: FxHashMap<u64, bool>,
 }
 
 impl CachedIsViable {
-    pub fn default() -> CachedIsViable {
-        CachedIsViable {
-            is_viable_cache: FxHashMap::default(),
-        }
-    }
-
     pub fn is_viable<T: Iterator<Item = char> + Clone>(&mut self, iter: T, trie: &Trie) -> bool {
         let mut hasher = FxHasher::default();
         for c in iter.clone() {
@@ -63,3 +51,62 @@ impl CachedIsViable {
             .or_insert_with(|| trie.is_viable(iter))
     }
 }
+
+#[derive(Clone, Default)]
+pub struct CachedEntropy {
+    entropy_cache: FxHashMap<u64, f64>,
+}
+
+impl CachedEntropy {
+    /// Returns the `min_letter_entropy` of the pattern, looking up its candidate words
+    /// in `words_cache` on a miss.
+    pub fn min_letter_entropy<T: Iterator<Item = char> + Clone>(
+        &mut self,
+        iter: T,
+        trie: &Trie,
+        words_cache: &mut CachedWords,
+    ) -> f64 {
+        let mut hasher = FxHasher::default();
+        for c in iter.clone() {
+            c.hash(&mut hasher);
+        }
+        let key = hasher.finish();
+
+        *self.entropy_cache.entry(key).or_insert_with(|| {
+            let words = words_cache.words(iter.clone(), trie);
+            min_letter_entropy(iter, words)
+        })
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CachedLetters {
+    letters_cache: FxHashMap<u64, FxHashSet<char>>,
+}
+
+impl CachedLetters {
+    /// Returns the letters the candidate words of the pattern have at `position`, looking
+    /// up the words in `words_cache` on a miss.
+    pub fn letters<T: Iterator<Item = char> + Clone>(
+        &mut self,
+        iter: T,
+        position: usize,
+        trie: &Trie,
+        words_cache: &mut CachedWords,
+    ) -> &FxHashSet<char> {
+        let mut hasher = FxHasher::default();
+        for c in iter.clone() {
+            c.hash(&mut hasher);
+        }
+        position.hash(&mut hasher);
+        let key = hasher.finish();
+
+        self.letters_cache.entry(key).or_insert_with(|| {
+            words_cache
+                .words(iter, trie)
+                .iter()
+                .filter_map(|word| word.chars().nth(position))
+                .collect()
+        })
+    }
+}
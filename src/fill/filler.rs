@@ -3,160 +3,2745 @@ An algorithm that composes algorithms and data structures throughout this
 crate. This is where the magic happens.
 */
 
-use rand::seq::SliceRandom;
-use std::{collections::HashSet, hash::BuildHasherDefault, time::Instant};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
 
-use rustc_hash::FxHasher;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
-    crossword::{Crossword, WordIterator},
-    parse::parse_word_boundaries,
+    crossword::{pattern_string, Crossword, Direction, WordIterator},
+    parse::{
+        parse_word_boundaries, parse_word_boundaries_with, BlockChars, WordBoundary, BLACK_SQUARE,
+        VOID_SQUARE,
+    },
     trie::Trie,
 };
 
 use super::{
     build_square_word_boundary_lookup,
-    cache::{CachedIsViable, CachedWords},
-    fill_one_word, is_viable_reuse, words_orthogonal_to_word, Fill,
+    cache::{CachedEntropy, CachedIsViable, CachedLetters, CachedWords},
+    fill_one_word, is_viable_reuse, word_key, words_orthogonal_to_word, CommittedWords, Fill,
+    FillError, FillStats, FillerState, Progress, RejectionReason, SlotHeuristic, TraceStep,
 };
 
+/// Scores a candidate word in the context of the grid it is placed in. Higher is better.
+pub type Scorer<'s> = Box<dyn Fn(&str, &Crossword) -> i32 + Send + Sync + 's>;
+
+/// A scorer shared by the fillers searching on other threads.
+type SharedScorer<'s> = Arc<dyn Fn(&str, &Crossword) -> i32 + Send + Sync + 's>;
+
+/// The cost of a partially filled grid, see `Filler::with_cost_fn`. Lower is better.
+pub type CostFn<'s> = Box<dyn Fn(&Crossword) -> u64 + Send + Sync + 's>;
+
+/// A cost function shared by the fillers searching on other threads.
+type SharedCostFn<'s> = Arc<dyn Fn(&Crossword) -> u64 + Send + Sync + 's>;
+
+/// Receives the progress of a fill, see `Filler::on_progress`.
+pub type ProgressFn<'s> = Box<dyn FnMut(Progress) + 's>;
+
+/// The number of candidates explored between two reports to `Filler::on_progress`.
+const PROGRESS_INTERVAL: usize = 10_000;
+
+/// The cost `Filler::viable_words_cost` adds for an open slot without viable words. A
+/// slot with `n` viable words adds `SLOT_COST / (n + 1)`.
+const SLOT_COST: u64 = 1_000_000;
+
+/// The viable words counted per slot by `Filler::viable_words_cost`. Slots with more words
+/// add next to nothing anyway.
+const SLOT_COST_MAX_WORDS: usize = 50;
+
+/// The number of templates `Filler::generate_and_fill` tries before giving up.
+const GENERATE_MAX_ATTEMPTS: u64 = 10;
+
+/// Stands for an open square outside the slots searched by `Filler::fill_direction`.
+const HIDDEN_SQUARE: char = '_';
+
+/// The configuration a `Filler` passes on to the fillers searching on other threads. Unlike
+/// the filler, whose progress callback may not be `Sync`, it can be shared with them.
+#[derive(Clone)]
+struct WorkerSettings<'s> {
+    trie: &'s Trie,
+    random: bool,
+    max_time_seconds: u64,
+    heuristic: SlotHeuristic,
+    start_slot_offset: usize,
+    scorer: Option<SharedScorer<'s>>,
+    cost_fn: Option<SharedCostFn<'s>>,
+    word_scores: bool,
+    letter_diversity: bool,
+    forbid_reversed: bool,
+    max_candidates_per_slot: Option<usize>,
+    prune_below_candidates: Option<usize>,
+    max_duplicate: usize,
+    blocklist: FxHashSet<String>,
+    required_words: Vec<String>,
+    block_chars: BlockChars,
+    themers: Vec<WordBoundary>,
+}
+
+impl<'s> WorkerSettings<'s> {
+    /// A filler with these settings and fresh caches, shuffling with a generator seeded by
+    /// `seed`, to search part of the grid on another thread.
+    fn worker(&self, seed: u64) -> Filler<'s> {
+        let mut worker = Filler::new(self.trie, self.random, self.max_time_seconds, false);
+        worker.heuristic = self.heuristic;
+        worker.start_slot_offset = self.start_slot_offset;
+        worker.scorer = self.scorer.clone();
+        worker.cost_fn = self.cost_fn.clone();
+        worker.word_scores = self.word_scores;
+        worker.letter_diversity = self.letter_diversity;
+        worker.forbid_reversed = self.forbid_reversed;
+        worker.max_candidates_per_slot = self.max_candidates_per_slot;
+        worker.prune_below_candidates = self.prune_below_candidates;
+        worker.max_duplicate = self.max_duplicate;
+        worker.blocklist = self.blocklist.clone();
+        worker.required_words = self.required_words.clone();
+        worker.block_chars = self.block_chars.clone();
+        worker.themers = self.themers.clone();
+        worker.rng = StdRng::seed_from_u64(seed);
+        worker
+    }
+}
+
 pub struct Filler<'s> {
     word_cache: CachedWords,
     is_viable_cache: CachedIsViable,
+    entropy_cache: CachedEntropy,
+    letters_cache: CachedLetters,
+
+    trie: &'s Trie,
+    random: bool,
+    max_time_seconds: u64,
+    heuristic: SlotHeuristic,
+    start_slot_offset: usize,
+    scorer: Option<SharedScorer<'s>>,
+    cost_fn: Option<SharedCostFn<'s>>,
+    word_scores: bool,
+    letter_diversity: bool,
+    strict: bool,
+    forbid_reversed: bool,
+    max_candidates_per_slot: Option<usize>,
+    prune_below_candidates: Option<usize>,
+    /// How many times a word can appear in the grid, 0 for any number of times.
+    max_duplicate: usize,
+    /// Uppercase words never placed in the grid, see `Filler::with_blocklist`.
+    blocklist: FxHashSet<String>,
+    /// Uppercase words every solution contains, see `Filler::with_required_words`.
+    required_words: Vec<String>,
+    block_chars: BlockChars,
+
+    themers: Vec<WordBoundary>,
+    themer_answers: Vec<(WordBoundary, String)>,
+    answers: Vec<(usize, Direction, String)>,
+
+    /// Shuffles the candidates of random fills, advancing across fills and steps.
+    rng: StdRng,
+    stats: FillStats,
+    progress: Option<ProgressFn<'s>>,
+    tracing: bool,
+    trace: Vec<TraceStep>,
+}
+
+impl<'s> Filler<'s> {
+    pub fn new(trie: &'s Trie, random: bool, max_time_seconds: u64, log: bool) -> Filler<'s> {
+        Filler {
+            word_cache: CachedWords::default(),
+            is_viable_cache: CachedIsViable::default(),
+            entropy_cache: CachedEntropy::default(),
+            letters_cache: CachedLetters::default(),
+            trie,
+            random,
+            max_time_seconds,
+            heuristic: SlotHeuristic::default(),
+            start_slot_offset: 0,
+            scorer: None,
+            cost_fn: None,
+            word_scores: false,
+            letter_diversity: false,
+            strict: false,
+            forbid_reversed: false,
+            max_candidates_per_slot: None,
+            prune_below_candidates: None,
+            max_duplicate: 1,
+            blocklist: FxHashSet::default(),
+            required_words: vec![],
+            block_chars: BlockChars::default(),
+            themers: vec![],
+            themer_answers: vec![],
+            answers: vec![],
+            rng: StdRng::from_rng(&mut rand::rng()),
+            stats: FillStats::default(),
+            progress: if log {
+                Some(Box::new(Filler::log_progress))
+            } else {
+                None
+            },
+            tracing: false,
+            trace: vec![],
+        }
+    }
+
+    /// A filler configured from a saved `state`, not logging. The grid to resume filling is
+    /// `FillerState::crossword`.
+    pub fn from_state(trie: &'s Trie, state: &FillerState) -> Filler<'s> {
+        let mut filler = Filler::new(trie, state.random, state.max_time_seconds, false);
+        filler.heuristic = state.heuristic;
+        filler.start_slot_offset = state.start_slot_offset;
+        filler.word_scores = state.word_scores;
+        filler.letter_diversity = state.letter_diversity;
+        filler.strict = state.strict;
+        filler.forbid_reversed = state.forbid_reversed;
+        filler.max_candidates_per_slot = state.max_candidates_per_slot;
+        filler.prune_below_candidates = state.prune_below_candidates;
+        filler.max_duplicate = state.max_duplicate;
+        filler.blocklist = state.blocklist.iter().cloned().collect();
+        filler.required_words = state.required_words.clone();
+        filler.block_chars = state.block_chars.clone();
+        filler.themers = state.themers.clone();
+        filler.answers = state.answers.clone();
+        filler
+    }
+
+    /// Saves `crossword`, the grid being filled, with the configuration of the filler but
+    /// the scorer and the cost function, to resume filling later with `Filler::from_state`.
+    pub fn state(&self, crossword: &Crossword) -> FillerState {
+        let mut blocklist: Vec<String> = self.blocklist.iter().cloned().collect();
+        blocklist.sort_unstable();
+        FillerState {
+            grid: crossword.to_string(),
+            random: self.random,
+            max_time_seconds: self.max_time_seconds,
+            heuristic: self.heuristic,
+            start_slot_offset: self.start_slot_offset,
+            word_scores: self.word_scores,
+            letter_diversity: self.letter_diversity,
+            strict: self.strict,
+            forbid_reversed: self.forbid_reversed,
+            max_candidates_per_slot: self.max_candidates_per_slot,
+            prune_below_candidates: self.prune_below_candidates,
+            max_duplicate: self.max_duplicate,
+            blocklist,
+            required_words: self.required_words.clone(),
+            block_chars: self.block_chars.clone(),
+            themers: self.themers.clone(),
+            answers: self.answers.clone(),
+        }
+    }
+
+    /// Seeds the generator shuffling the candidates of random fills, so that fills of the
+    /// same grid with the same word list and seed give the same solution. Defaults to a
+    /// generator seeded from the thread generator. `fill_parallel` still varies.
+    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Sets how the next slot to fill is chosen. Defaults to `SlotHeuristic::FewestCandidates`.
+    pub fn with_heuristic(&mut self, heuristic: SlotHeuristic) -> &mut Self {
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// Rotates which slot is filled first among slots the heuristic finds equally
+    /// constrained. Ties go to the slot starting closest after the square `offset`
+    /// squares from the top left, in reading order, wrapping around. Defaults to 0, the
+    /// top left square. Fills from different offsets tend to diverge more than random
+    /// fills alone.
+    pub fn with_start_slot_offset(&mut self, offset: usize) -> &mut Self {
+        self.start_slot_offset = offset;
+        self
+    }
+
+    /// Sets a scorer used to order the candidate words of a slot: words with a higher score
+    /// for the grid they are placed in are tried first. When `random` is set, candidates
+    /// with equal scores are still tried in random order.
+    pub fn with_scorer(&mut self, scorer: Scorer<'s>) -> &mut Self {
+        self.scorer = Some(Arc::from(scorer));
+        self
+    }
+
+    /// Sets a cost function used to order the grids reached by filling a slot: the grid
+    /// with the lowest cost is explored next, before backtracking to the others. Ties keep
+    /// the order of the candidate words. See `Filler::viable_words_cost` for a cost that
+    /// avoids dead ends.
+    pub fn with_cost_fn(&mut self, cost_fn: CostFn<'s>) -> &mut Self {
+        self.cost_fn = Some(Arc::from(cost_fn));
+        self
+    }
+
+    /// A cost for `with_cost_fn` penalizing the open slots of a grid with few viable words
+    /// in `trie`, most of all the ones without any. Grids likely to lead to a dead end are
+    /// then explored last. Slots are terminated by the default black squares.
+    pub fn viable_words_cost(trie: &'s Trie) -> CostFn<'s> {
+        Box::new(move |crossword| {
+            parse_word_boundaries(crossword)
+                .iter()
+                .map(|wb| WordIterator::new(crossword, wb))
+                .filter(|iter| iter.clone().any(|c| c == ' '))
+                .map(|iter| SLOT_COST / (trie.count_words(iter, SLOT_COST_MAX_WORDS) as u64 + 1))
+                .sum()
+        })
+    }
+
+    /// When set, the candidate words of a slot are tried by descending score in the trie,
+    /// e.g. to prefer common answers with a trie built from word frequencies, see
+    /// `Trie::build_scored`. A scorer set with `with_scorer` takes precedence. When
+    /// `random` is set, candidates with equal scores are still tried in random order.
+    /// Defaults to false.
+    pub fn with_word_scores(&mut self, word_scores: bool) -> &mut Self {
+        self.word_scores = word_scores;
+        self
+    }
+
+    /// When set, candidate words of equal score, or of any score without a scorer or word
+    /// scores, are tried by descending letter diversity: words with more distinct letters,
+    /// fewer of which are already in the slots crossing the slot, first. This avoids dull
+    /// fills repeating the same letters. Defaults to false.
+    pub fn with_letter_diversity(&mut self, letter_diversity: bool) -> &mut Self {
+        self.letter_diversity = letter_diversity;
+        self
+    }
+
+    /// In strict mode, `fill` first checks that the letters given in the input grid can be
+    /// completed by some word in every slot, and fails right away naming the first slot
+    /// that can't, instead of searching until the time limit.
+    pub fn with_strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
+    /// When set, a word is never placed in a grid that contains its reverse elsewhere,
+    /// e.g. `TAB` is not placed next to `BAT`. Defaults to false.
+    pub fn with_forbid_reversed(&mut self, forbid_reversed: bool) -> &mut Self {
+        self.forbid_reversed = forbid_reversed;
+        self
+    }
+
+    /// Tries at most `max_candidates` words in each slot, the ones that would be tried
+    /// first, i.e. the best ones with a scorer. This can speed up dense fills a lot,
+    /// but the search is no longer complete: a grid that has solutions may then fail with
+    /// `FillError::NoSolution`. Defaults to trying every word.
+    pub fn with_max_candidates_per_slot(&mut self, max_candidates: usize) -> &mut Self {
+        self.max_candidates_per_slot = Some(max_candidates);
+        self
+    }
+
+    /// Discards a candidate grid as soon as one of the open slots crossing the word just
+    /// placed has fewer than `min_candidates` words left, instead of finding out that the
+    /// slot can't be filled deeper in the search. The search is no longer complete for
+    /// `min_candidates` above 1, and grids that could be filled get discarded: small
+    /// thresholds such as 2 or 3 work best. Defaults to discarding only slots without any
+    /// word.
+    pub fn with_prune_below_candidates(&mut self, min_candidates: usize) -> &mut Self {
+        self.prune_below_candidates = Some(min_candidates);
+        self
+    }
+
+    /// Allows a word to appear up to `max_duplicate` times in the grid, e.g. 2 for small
+    /// grids with few words to choose from, or any number of times with 0. Defaults to 1,
+    /// forbidding repeated words.
+    pub fn with_max_duplicate(&mut self, max_duplicate: usize) -> &mut Self {
+        self.max_duplicate = max_duplicate;
+        self
+    }
+
+    /// Words that are never placed in the grid even though they are in the word list,
+    /// e.g. offensive words. Neither the filled words nor the crossing words they complete
+    /// are blocklisted words. The comparison is case-insensitive. Words given in the input
+    /// grid are kept. Defaults to no words.
+    pub fn with_blocklist(&mut self, words: HashSet<String>) -> &mut Self {
+        self.blocklist = words.iter().map(|word| word.to_uppercase()).collect();
+        self
+    }
+
+    /// Words that every solution contains, across or down, e.g. answers a constructor
+    /// wants somewhere in the grid. The comparison is case-insensitive. A partial grid is
+    /// abandoned as soon as some required word no longer fits any of its slots. `fill`
+    /// fails with `FillError::InvalidGrid` if a required word has no slot of its length,
+    /// e.g. when it is longer than every slot. Defaults to no words.
+    pub fn with_required_words(&mut self, words: Vec<String>) -> &mut Self {
+        self.required_words = words.iter().map(|word| word.to_uppercase()).collect();
+        self
+    }
+
+    /// Sets which squares terminate words while filling. Black squares that are not in
+    /// `block_chars` are filled like open squares, e.g. to fill the `:` squares of a
+    /// diagramless puzzle. Defaults to both `.` and `:`.
+    pub fn with_block_chars(&mut self, block_chars: BlockChars) -> &mut Self {
+        self.block_chars = block_chars;
+        self
+    }
+
+    /// Clears the trace, checks the word list, writes the known answers in the grid,
+    /// validates it in strict mode, and opens the black squares that don't terminate words.
+    /// Grids with rebus squares are rejected, as slots are filled one letter per square.
+    fn prepare(&mut self, crossword: &Crossword) -> Result<Crossword, FillError> {
+        self.trace.clear();
+        if self.trie.is_empty() {
+            return Err(FillError::EmptyDictionary);
+        }
+        if let Some((row, col)) = crossword.rebus.keys().next() {
+            return Err(FillError::InvalidGrid(format!(
+                "Rebus square at row {}, column {} cannot be filled",
+                row, col
+            )));
+        }
+        let crossword = self.write_answers(crossword)?;
+        if self.strict {
+            self.validate_givens(&crossword)
+                .map_err(FillError::InvalidGrid)?;
+        }
+        let crossword = self.open_unblocked_squares(&crossword);
+        let word_boundaries = parse_word_boundaries_with(&crossword, &self.block_chars);
+        for word in &self.required_words {
+            let length = word.chars().count();
+            if !word_boundaries.iter().any(|wb| wb.length == length) {
+                return Err(FillError::InvalidGrid(format!(
+                    "Required word {} fits no slot of the grid",
+                    word
+                )));
+            }
+        }
+        Ok(crossword)
+    }
+
+    /// Returns the grid with the black squares that don't terminate words opened.
+    fn open_unblocked_squares(&self, crossword: &Crossword) -> Crossword {
+        let mut crossword = crossword.clone();
+        for c in crossword.contents.iter_mut() {
+            if BLACK_SQUARE.contains(c) && !self.block_chars.contains(*c) {
+                *c = ' ';
+            }
+        }
+        crossword
+    }
+
+    /// Checks that every partially filled slot of the grid can still be completed by some
+    /// word. Err names the first slot, in clue order, whose given letters no word matches.
+    pub fn validate_givens(&mut self, crossword: &Crossword) -> Result<(), String> {
+        for entry in crossword.numbered_entries() {
+            let iter = WordIterator::new(crossword, &entry.word_boundary);
+            if iter.clone().all(|c| c == ' ') {
+                continue;
+            }
+            if !self.is_viable_cache.is_viable(iter, self.trie) {
+                return Err(format!(
+                    "No word matches the given letters of {} {:?}: `{}`",
+                    entry.number,
+                    entry.word_boundary.direction,
+                    pattern_string(crossword, &entry.word_boundary, '_')
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts the words crossing `word_boundary` that no word could complete anymore once
+    /// `word` is placed in `word_boundary`. This is the lookahead a slot heuristic relies
+    /// on, exposed to explain why a word is a poor choice.
+    ///
+    /// Panics if `word` does not have the length of `word_boundary`.
+    pub fn word_cost(
+        &mut self,
+        crossword: &Crossword,
+        word_boundary: &WordBoundary,
+        word: &str,
+    ) -> usize {
+        assert_eq!(
+            word_boundary.length,
+            word.chars().count(),
+            "{} does not fit a slot of length {}",
+            word,
+            word_boundary.length
+        );
+        let crossword = self.open_unblocked_squares(crossword);
+        let placed = fill_one_word(
+            &crossword,
+            &WordIterator::new(&crossword, word_boundary),
+            word,
+        );
+        let word_boundaries = parse_word_boundaries_with(&crossword, &self.block_chars);
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+        words_orthogonal_to_word(word_boundary, &word_boundary_lookup)
+            .into_iter()
+            .filter(|crossing| {
+                !self
+                    .is_viable_cache
+                    .is_viable(WordIterator::new(&placed, crossing), self.trie)
+            })
+            .count()
+    }
+
+    /// Marks slots as theme entries. Themers are filled before any other slot and,
+    /// once filled, their answers are locked: the search never backtracks to try
+    /// another word in a themer. This makes the search incomplete, so a grid that
+    /// could be filled with other themer answers may fail to fill.
+    ///
+    /// Themers that are already complete in the input grid are kept as given.
+    pub fn with_themers(&mut self, themers: Vec<WordBoundary>) -> &mut Self {
+        self.themers = themers;
+        self
+    }
+
+    /// Calls `callback` with the progress of the search every 10,000 candidates, starting
+    /// with the first one, e.g. to show the grid being filled. Replaces the progress
+    /// written to stderr by fillers created with logging.
+    pub fn on_progress(&mut self, callback: ProgressFn<'s>) -> &mut Self {
+        self.progress = Some(callback);
+        self
+    }
+
+    /// Records the order in which the slots of a solution were filled, see `trace`.
+    /// The trace isn't recorded by `fill_parallel_deterministic`. Defaults to false.
+    pub fn with_trace(&mut self, tracing: bool) -> &mut Self {
+        self.tracing = tracing;
+        self
+    }
+
+    /// The slots of the last solution found while tracing, in the order they were
+    /// completed: each slot picked by the search, followed by the crossing slots its word
+    /// completed, in clue order. Slots filled in the input grid are left out. Empty if no
+    /// solution was found.
+    pub fn trace(&self) -> &[TraceStep] {
+        &self.trace
+    }
+
+    /// Statistics of the last fill, successful or not, by any of the fill methods.
+    pub fn stats(&self) -> FillStats {
+        self.stats
+    }
+
+    /// The answers in the themer slots of the last successful fill, in the order
+    /// the themers were given.
+    pub fn themer_answers(&self) -> &[(WordBoundary, String)] {
+        &self.themer_answers
+    }
+
+    /// Sets known answers as `(number, direction, answer)`, numbered like the clues of
+    /// the grid to fill. The answers are written in the grid before filling and are
+    /// then kept like any given letters. `fill` fails if an answer names no slot,
+    /// doesn't fit its slot, or contradicts the given letters.
+    pub fn with_answers(&mut self, answers: Vec<(usize, Direction, String)>) -> &mut Self {
+        self.answers = answers;
+        self
+    }
+
+    /// Returns the grid with the known answers written in.
+    fn write_answers(&self, crossword: &Crossword) -> Result<Crossword, FillError> {
+        if self.answers.is_empty() {
+            return Ok(crossword.clone());
+        }
+        let entries = crossword.numbered_entries();
+        let mut result = crossword.clone();
+        for (number, direction, answer) in &self.answers {
+            let entry = entries
+                .iter()
+                .find(|entry| {
+                    entry.number == *number && entry.word_boundary.direction == *direction
+                })
+                .ok_or_else(|| {
+                    FillError::InvalidGrid(format!(
+                        "No slot {} {:?} in the grid",
+                        number, direction
+                    ))
+                })?;
+            let answer = answer.to_uppercase();
+            let iter = WordIterator::new(&result, &entry.word_boundary);
+            if answer.chars().count() != entry.word_boundary.length {
+                return Err(FillError::InvalidGrid(format!(
+                    "Answer {} does not fit the {} squares of {} {:?}",
+                    answer, entry.word_boundary.length, number, direction
+                )));
+            }
+            if iter
+                .clone()
+                .zip(answer.chars())
+                .any(|(given, c)| given != ' ' && given != c)
+            {
+                return Err(FillError::InvalidGrid(format!(
+                    "Answer {} contradicts the given letters of {} {:?}: `{}`",
+                    answer,
+                    number,
+                    direction,
+                    pattern_string(&result, &entry.word_boundary, '_')
+                )));
+            }
+            result = fill_one_word(&result, &iter, &answer);
+        }
+        Ok(result)
+    }
+
+    /// Orders the candidate words of a slot so that the ones to try first come last,
+    /// matching the stack of candidates the filler explores, and keeps at most
+    /// `max_candidates_per_slot` of them.
+    fn order_fills(
+        &mut self,
+        potential_fills: &mut Vec<String>,
+        candidate: &Crossword,
+        slot: &WordBoundary,
+    ) {
+        if self.random {
+            potential_fills.shuffle(&mut self.rng);
+        }
+        if self.scorer.is_some() || self.word_scores || self.letter_diversity {
+            let crossings = if self.letter_diversity {
+                self.crossing_letters(candidate, slot)
+            } else {
+                FxHashMap::default()
+            };
+            potential_fills.sort_by_cached_key(|word| {
+                let score = match &self.scorer {
+                    Some(scorer) => scorer(word, candidate),
+                    None if self.word_scores => self.trie.score(word).unwrap_or(0),
+                    None => 0,
+                };
+                (score, Filler::letter_diversity(word, &crossings))
+            });
+        }
+        if let Some(max_candidates) = self.max_candidates_per_slot {
+            let excess = potential_fills.len().saturating_sub(max_candidates);
+            potential_fills.drain(..excess);
+        }
+    }
+
+    /// Picks the unfilled slot to fill next according to the heuristic, themers first.
+    /// Ties are broken by the position of the slot, see `with_start_slot_offset`.
+    fn select_slot<'c>(
+        &mut self,
+        candidate: &'c Crossword,
+        word_boundaries: &'c [WordBoundary],
+    ) -> Option<WordIterator<'c>> {
+        let squares = candidate.width * candidate.height;
+        let offset = self.start_slot_offset % squares;
+        let tie_break = |word_boundary: &WordBoundary| {
+            let square = word_boundary.start_row * candidate.width + word_boundary.start_col;
+            (square + squares - offset) % squares
+        };
+
+        let open_slots = word_boundaries
+            .iter()
+            .map(|word_boundary| WordIterator::new(candidate, word_boundary))
+            .filter(|iter| iter.clone().any(|c| c == ' '));
+
+        match self.heuristic {
+            SlotHeuristic::FewestCandidates => open_slots.min_by_key(|iter| {
+                let is_themer = self.themers.contains(iter.word_boundary);
+                let words = self.word_cache.words(iter.clone(), self.trie);
+                (!is_themer, words.len(), tie_break(iter.word_boundary))
+            }),
+            SlotHeuristic::MinimumEntropy => open_slots
+                .map(|iter| {
+                    let is_themer = self.themers.contains(iter.word_boundary);
+                    let entropy = self.entropy_cache.min_letter_entropy(
+                        iter.clone(),
+                        self.trie,
+                        &mut self.word_cache,
+                    );
+                    (!is_themer, entropy, iter)
+                })
+                .min_by(|(a_themer, a_entropy, a), (b_themer, b_entropy, b)| {
+                    a_themer
+                        .cmp(b_themer)
+                        .then(a_entropy.total_cmp(b_entropy))
+                        .then(tie_break(a.word_boundary).cmp(&tie_break(b.word_boundary)))
+                })
+                .map(|(_, _, iter)| iter),
+            SlotHeuristic::AcrossFirst => open_slots.min_by_key(|iter| {
+                let is_themer = self.themers.contains(iter.word_boundary);
+                let is_down = iter.word_boundary.direction == Direction::Down;
+                (!is_themer, is_down, tie_break(iter.word_boundary))
+            }),
+            SlotHeuristic::LongestFirst => open_slots.min_by_key(|iter| {
+                let is_themer = self.themers.contains(iter.word_boundary);
+                let words = self.word_cache.words(iter.clone(), self.trie);
+                (
+                    !is_themer,
+                    Reverse(iter.word_boundary.length),
+                    words.len(),
+                    tie_break(iter.word_boundary),
+                )
+            }),
+        }
+    }
+
+    fn record_themer_answers(&mut self, solution: &Crossword) {
+        self.themer_answers = self
+            .themers
+            .iter()
+            .map(|themer| (themer.clone(), pattern_string(solution, themer, ' ')))
+            .collect();
+    }
+
+    /// Generates a symmetric template with `Crossword::generate_template` and fills it,
+    /// trying new templates until one can be filled. Each attempt gets the full time
+    /// budget. Err is returned if none of the attempted templates could be filled.
+    pub fn generate_and_fill(
+        &mut self,
+        width: usize,
+        height: usize,
+        block_ratio: f64,
+        seed: u64,
+    ) -> Result<Crossword, String> {
+        for attempt in 0..GENERATE_MAX_ATTEMPTS {
+            let template = Crossword::generate_template(
+                width,
+                height,
+                block_ratio,
+                seed.wrapping_add(attempt),
+            )?;
+            match self.fill(&template) {
+                Ok(crossword) => return Ok(crossword),
+                Err(e @ FillError::EmptyDictionary) => return Err(e.into()),
+                Err(_) => {}
+            }
+        }
+        Err(format!(
+            "Could not fill any of {} generated templates",
+            GENERATE_MAX_ATTEMPTS
+        ))
+    }
+
+    /// Fills the grid on `num_threads` threads, always returning the same solution for
+    /// the same `seed`, whatever the number of threads.
+    ///
+    /// The candidate words of the first slot are ordered once and dealt round-robin to the
+    /// threads. Each thread searches its branches in order with its own caches and a random
+    /// generator seeded from `seed` and the branch index, and the solution of the earliest
+    /// branch wins, so the result does not depend on which thread finishes first. Only a
+    /// timeout can make the outcome depend on the machine.
+    pub fn fill_parallel_deterministic(
+        &mut self,
+        initial_crossword: &Crossword,
+        num_threads: usize,
+        seed: u64,
+    ) -> Result<Crossword, FillError> {
+        let start_time = Instant::now();
+        self.stats = FillStats::default();
+
+        self.themer_answers.clear();
+
+        let initial_crossword = &self.prepare(initial_crossword)?;
+        let word_boundaries = parse_word_boundaries_with(initial_crossword, &self.block_chars);
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+
+        let settings = &self.worker_settings();
+        let mut root = settings.worker(seed);
+        let committed = root.committed_words(initial_crossword, &word_boundaries);
+        let (_, branches) = root.expand(
+            initial_crossword,
+            &committed,
+            &word_boundaries,
+            &word_boundary_lookup,
+        )?;
+        // expanded candidates are ordered to be popped from the end, and each branch is
+        // searched from scratch
+        let branches: Vec<Crossword> = branches.into_iter().rev().map(|(c, _)| c).collect();
+
+        self.stats.candidate_count = 1;
+        if let Some(solution) = branches.iter().find(|b| !b.contents.contains(&' ')) {
+            let solution = solution.clone();
+            self.record_themer_answers(&solution);
+            self.stats.elapsed = start_time.elapsed();
+            return Ok(solution);
+        }
+
+        let num_threads = num_threads.max(1);
+        let solved = AtomicUsize::new(usize::MAX);
+        let (mut results, candidate_count): (Vec<_>, usize) = thread::scope(|scope| {
+            let handles: Vec<_> = (0..num_threads)
+                .map(|thread_index| {
+                    let (branches, word_boundaries, word_boundary_lookup, solved) =
+                        (&branches, &word_boundaries, &word_boundary_lookup, &solved);
+                    scope.spawn(move || {
+                        let mut results = vec![];
+                        let mut worker = settings.worker(seed);
+                        for index in (thread_index..branches.len()).step_by(num_threads) {
+                            if index > solved.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            worker.rng = StdRng::seed_from_u64(seed.wrapping_add(index as u64 + 1));
+                            let result = worker.search_first(
+                                &branches[index],
+                                word_boundaries,
+                                word_boundary_lookup,
+                                start_time,
+                                &|| solved.load(Ordering::Relaxed) < index,
+                            );
+                            if result.is_ok() {
+                                solved.fetch_min(index, Ordering::Relaxed);
+                            }
+                            results.push((index, result));
+                        }
+                        (results, worker.stats.candidate_count)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .fold((vec![], 0), |(mut all_results, candidate_count), handle| {
+                    let (results, count) = handle.join().expect("Filler thread panicked");
+                    all_results.extend(results);
+                    (all_results, candidate_count + count)
+                })
+        });
+        self.stats.candidate_count += candidate_count;
+        self.stats.elapsed = start_time.elapsed();
+
+        results.sort_by_key(|(index, _): &(usize, Result<Crossword, FillError>)| *index);
+        if let Some((_, Ok(solution))) = results.iter().find(|(_, result)| result.is_ok()) {
+            let solution = solution.clone();
+            self.record_themer_answers(&solution);
+            return Ok(solution);
+        }
+
+        Err(Filler::best_error(
+            results.into_iter().filter_map(|(_, result)| result.err()),
+        ))
+    }
+
+    /// Fills the grid on `num_threads` threads, returning the first solution any thread
+    /// finds. Faster than `fill_parallel_deterministic` on grids with few solutions, but
+    /// the solution may change from run to run.
+    ///
+    /// The candidate words of the first slot are ordered once, and each thread searches
+    /// the next branch not taken yet whenever it exhausts one, with its own caches, so
+    /// threads that finish early keep working. The other threads stop as soon as a
+    /// solution is found.
+    pub fn fill_parallel(
+        &mut self,
+        initial_crossword: &Crossword,
+        num_threads: usize,
+    ) -> Result<Crossword, FillError> {
+        let start_time = Instant::now();
+        self.stats = FillStats::default();
+
+        self.themer_answers.clear();
+
+        let initial_crossword = &self.prepare(initial_crossword)?;
+        let word_boundaries = parse_word_boundaries_with(initial_crossword, &self.block_chars);
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+
+        let settings = &self.worker_settings();
+        let mut root = settings.worker(rand::random());
+        let committed = root.committed_words(initial_crossword, &word_boundaries);
+        let (_, branches) = root.expand(
+            initial_crossword,
+            &committed,
+            &word_boundaries,
+            &word_boundary_lookup,
+        )?;
+        // expanded candidates are ordered to be popped from the end, and each branch is
+        // searched from scratch
+        let branches: Vec<Crossword> = branches.into_iter().rev().map(|(c, _)| c).collect();
+
+        self.stats.candidate_count = 1;
+        if let Some(solution) = branches.iter().find(|b| !b.contents.contains(&' ')) {
+            let solution = solution.clone();
+            self.record_themer_answers(&solution);
+            self.stats.elapsed = start_time.elapsed();
+            return Ok(solution);
+        }
+
+        let next_branch = AtomicUsize::new(0);
+        let solved = AtomicBool::new(false);
+        let (results, candidate_count): (Vec<_>, usize) = thread::scope(|scope| {
+            let handles: Vec<_> = (0..num_threads.max(1))
+                .map(|_| {
+                    let (branches, word_boundaries, word_boundary_lookup) =
+                        (&branches, &word_boundaries, &word_boundary_lookup);
+                    let (next_branch, solved) = (&next_branch, &solved);
+                    scope.spawn(move || {
+                        let mut errors = vec![];
+                        let mut worker = settings.worker(rand::random());
+                        while !solved.load(Ordering::Relaxed) {
+                            let index = next_branch.fetch_add(1, Ordering::Relaxed);
+                            if index >= branches.len() {
+                                break;
+                            }
+                            match worker.search_first(
+                                &branches[index],
+                                word_boundaries,
+                                word_boundary_lookup,
+                                start_time,
+                                &|| solved.load(Ordering::Relaxed),
+                            ) {
+                                Ok(solution) => {
+                                    solved.store(true, Ordering::Relaxed);
+                                    return (Ok(solution), worker.stats.candidate_count);
+                                }
+                                Err(error) => errors.push(error),
+                            }
+                        }
+                        let error = Filler::best_error(errors.into_iter());
+                        (Err(error), worker.stats.candidate_count)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .fold((vec![], 0), |(mut results, candidate_count), handle| {
+                    let (result, count) = handle.join().expect("Filler thread panicked");
+                    results.push(result);
+                    (results, candidate_count + count)
+                })
+        });
+        self.stats.candidate_count += candidate_count;
+        self.stats.elapsed = start_time.elapsed();
+
+        let mut errors = vec![];
+        for result in results {
+            match result {
+                Ok(solution) => {
+                    self.record_themer_answers(&solution);
+                    return Ok(solution);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+        Err(Filler::best_error(errors.into_iter()))
+    }
+
+    /// The error to report when every branch of a parallel fill failed: the timeout with
+    /// the most filled grid, the earliest on ties, or `FillError::NoSolution`.
+    fn best_error(errors: impl Iterator<Item = FillError>) -> FillError {
+        errors
+            .filter(|error| matches!(error, FillError::Timeout { .. }))
+            .fold(None, |best: Option<FillError>, timeout| {
+                match (&best, &timeout) {
+                    (
+                        Some(FillError::Timeout {
+                            filled_fraction: best_fraction,
+                            ..
+                        }),
+                        FillError::Timeout {
+                            filled_fraction, ..
+                        },
+                    ) if best_fraction >= filled_fraction => best,
+                    _ => Some(timeout),
+                }
+            })
+            .unwrap_or(FillError::NoSolution)
+    }
+
+    /// Sends every solution of the grid to `tx` as soon as it is found, until all
+    /// candidates are explored, the time limit is reached or the receiver hangs up.
+    /// Returns the number of solutions sent.
+    ///
+    /// Err is only returned if the grid cannot be filled as given.
+    pub fn fill_to_channel(
+        &mut self,
+        initial_crossword: &Crossword,
+        tx: Sender<Crossword>,
+    ) -> Result<usize, FillError> {
+        let start_time = Instant::now();
+        self.stats = FillStats::default();
+
+        self.themer_answers.clear();
+
+        let initial_crossword = &self.prepare(initial_crossword)?;
+        let word_boundaries = parse_word_boundaries_with(initial_crossword, &self.block_chars);
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+
+        let mut sent = 0;
+        let result = self.search(
+            initial_crossword,
+            &word_boundaries,
+            &word_boundary_lookup,
+            start_time,
+            &|| false,
+            &mut |solution| {
+                let receiving = tx.send(solution).is_ok();
+                if receiving {
+                    sent += 1;
+                }
+                receiving
+            },
+        );
+        self.stats.elapsed = start_time.elapsed();
+        match result {
+            Ok(_) | Err(FillError::Timeout { .. }) => Ok(sent),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Counts the solutions of the grid, with words repeated only as allowed by
+    /// `Filler::with_max_duplicate`, stopping as soon as `limit` are found.
+    ///
+    /// Err is returned if the grid cannot be filled as given, or if the time limit is
+    /// reached before the count is known.
+    pub fn count_solutions(
+        &mut self,
+        initial_crossword: &Crossword,
+        limit: usize,
+    ) -> Result<usize, FillError> {
+        let start_time = Instant::now();
+        self.stats = FillStats::default();
+
+        self.themer_answers.clear();
+
+        let initial_crossword = &self.prepare(initial_crossword)?;
+        let word_boundaries = parse_word_boundaries_with(initial_crossword, &self.block_chars);
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+
+        let mut count = 0;
+        let result = if limit == 0 {
+            Ok(false)
+        } else {
+            self.search(
+                initial_crossword,
+                &word_boundaries,
+                &word_boundary_lookup,
+                start_time,
+                &|| false,
+                &mut |_| {
+                    count += 1;
+                    count < limit
+                },
+            )
+        };
+        self.stats.elapsed = start_time.elapsed();
+        result.map(|_| count)
+    }
+
+    /// Returns up to `limit` distinct solutions of the grid, in the order the search finds
+    /// them. The time limit applies to the whole enumeration: on timeout, or if the grid
+    /// can't be filled, the solutions found so far are returned, possibly none. Solutions
+    /// only repeat words as allowed by `Filler::with_max_duplicate`.
+    pub fn fill_all(&mut self, initial_crossword: &Crossword, limit: usize) -> Vec<Crossword> {
+        let start_time = Instant::now();
+        self.stats = FillStats::default();
+
+        self.themer_answers.clear();
+
+        let mut solutions = vec![];
+        let initial_crossword = match self.prepare(initial_crossword) {
+            Ok(crossword) => crossword,
+            Err(_) => return solutions,
+        };
+        let word_boundaries = parse_word_boundaries_with(&initial_crossword, &self.block_chars);
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+
+        if limit > 0 {
+            let mut seen = FxHashSet::default();
+            // the solutions found before an error are kept
+            let _ = self.search(
+                &initial_crossword,
+                &word_boundaries,
+                &word_boundary_lookup,
+                start_time,
+                &|| false,
+                &mut |solution| {
+                    if seen.insert(solution.clone()) {
+                        solutions.push(solution);
+                    }
+                    solutions.len() < limit
+                },
+            );
+        }
+        self.stats.elapsed = start_time.elapsed();
+        solutions
+    }
+
+    /// Whether the grid has exactly one solution with the word list. False is also
+    /// returned if the grid cannot be filled as given or the time limit is reached.
+    pub fn has_unique_solution(&mut self, crossword: &Crossword) -> bool {
+        self.count_solutions(crossword, 2) == Ok(1)
+    }
+
+    /// Fills the grid like `fill`, returning the solution and true, or on timeout the most
+    /// filled grid the search reached and false, e.g. to finish a large grid by hand. Grids
+    /// that can't be filled are returned as given, with false.
+    pub fn fill_best_effort(&mut self, crossword: &Crossword) -> (Crossword, bool) {
+        match self.fill(crossword) {
+            Ok(solution) => (solution, true),
+            Err(FillError::Timeout { best_partial, .. }) => (best_partial, false),
+            Err(_) => (crossword.clone(), false),
+        }
+    }
+
+    /// Places a single word, in the slot `fill` would fill next, and returns the slot and
+    /// the grid with the word, e.g. to fill a grid interactively. The word is the first
+    /// candidate `fill` would try that keeps every crossing viable. Random fillers draw
+    /// from the same random generator at every step, so fillers seeded alike take the
+    /// same steps. Steps never backtrack: Err `NoSolution` is returned if no word fits the
+    /// slot, and `InvalidGrid` if the grid has no open slot left.
+    pub fn step(&mut self, crossword: &Crossword) -> Result<(WordBoundary, Crossword), FillError> {
+        let crossword = self.prepare(crossword)?;
+        let word_boundaries = parse_word_boundaries_with(&crossword, &self.block_chars);
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+        let committed = self.committed_words(&crossword, &word_boundaries);
+        let (slot, mut candidates) = self.expand(
+            &crossword,
+            &committed,
+            &word_boundaries,
+            &word_boundary_lookup,
+        )?;
+        candidates
+            .pop()
+            .map(|(candidate, _)| (slot, candidate))
+            .ok_or(FillError::NoSolution)
+    }
+
+    /// Explains why `word` can't be placed in the slot `boundary` of `grid`, e.g. to tell
+    /// the user of a construction tool why the word they tried doesn't fit. The length is
+    /// checked first, then the given letters and then the crossing slots, in word order.
+    /// Returns the first reason found, or `None` if the word can be placed.
+    pub fn explain_rejection(
+        &mut self,
+        grid: &Crossword,
+        boundary: &WordBoundary,
+        word: &str,
+    ) -> Option<RejectionReason> {
+        let word: Vec<char> = word.to_uppercase().chars().collect();
+        if word.len() != boundary.length {
+            return Some(RejectionReason::WrongLength {
+                expected: boundary.length,
+                length: word.len(),
+            });
+        }
+
+        let crossword = self.open_unblocked_squares(grid);
+        for (index, (row, col)) in boundary.squares().enumerate() {
+            let given = crossword.contents[row * crossword.width + col];
+            if given != ' ' && given != word[index] {
+                return Some(RejectionReason::ConflictsWithGiven { index, given });
+            }
+        }
+
+        let word: String = word.into_iter().collect();
+        let with_word = fill_one_word(&crossword, &WordIterator::new(&crossword, boundary), &word);
+        let word_boundaries = parse_word_boundaries_with(&crossword, &self.block_chars);
+        let crossing_direction = match boundary.direction {
+            Direction::Across => Direction::Down,
+            Direction::Down => Direction::Across,
+        };
+        for (index, (row, col)) in boundary.squares().enumerate() {
+            let crossing = word_boundaries.iter().find(|wb| {
+                wb.direction == crossing_direction && wb.squares().any(|sq| sq == (row, col))
+            });
+            if let Some(crossing) = crossing {
+                let iter = WordIterator::new(&with_word, crossing);
+                if !self.is_viable_cache.is_viable(iter, self.trie) {
+                    return Some(RejectionReason::KillsCrossing {
+                        index,
+                        crossing: crossing.clone(),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Counts the words of the word list that fit each open slot of the grid, but for the
+    /// blocklisted words, e.g. to find the most constrained slots while designing a grid.
+    /// Slots with no word can't be filled. The across slots come first, row by row, then
+    /// the down slots column by column, which isn't their clue order.
+    pub fn slot_constraints(&self, crossword: &Crossword) -> Vec<(WordBoundary, usize)> {
+        let crossword = self.open_unblocked_squares(crossword);
+        parse_word_boundaries_with(&crossword, &self.block_chars)
+            .into_iter()
+            .filter_map(|word_boundary| {
+                let iter = WordIterator::new(&crossword, &word_boundary);
+                if !iter.clone().any(|c| c == ' ') {
+                    return None;
+                }
+                let count = self
+                    .trie
+                    .words(iter)
+                    .iter()
+                    .filter(|word| !self.blocklist.contains(*word))
+                    .count();
+                Some((word_boundary, count))
+            })
+            .collect()
+    }
+
+    /// Fills only the slots running in `direction`, keeping the given letters, without
+    /// checking that the crossing slots can still be filled. The crossing slots can be
+    /// filled later, for example from another word list. Squares outside the filled slots
+    /// are left open.
+    pub fn fill_direction(
+        &mut self,
+        initial_crossword: &Crossword,
+        direction: Direction,
+    ) -> Result<Crossword, FillError> {
+        let start_time = Instant::now();
+        self.stats = FillStats::default();
+
+        self.themer_answers.clear();
+
+        let mut crossword = self.prepare(initial_crossword)?;
+        let word_boundaries: Vec<WordBoundary> =
+            parse_word_boundaries_with(&crossword, &self.block_chars)
+                .into_iter()
+                .filter(|word_boundary| word_boundary.direction == direction)
+                .collect();
+        // without crossing slots in the lookup, no crossing slot is checked for viability
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+
+        // the search stops once no square is open, so hide the squares outside the slots
+        let mut in_slot = vec![false; crossword.contents.len()];
+        for word_boundary in word_boundaries.iter() {
+            for (row, col) in word_boundary.squares() {
+                in_slot[row * crossword.width + col] = true;
+            }
+        }
+        let hidden: Vec<usize> = (0..crossword.contents.len())
+            .filter(|square| !in_slot[*square] && crossword.contents[*square] == ' ')
+            .collect();
+        for square in hidden.iter() {
+            crossword.contents[*square] = HIDDEN_SQUARE;
+        }
+        let reveal = |mut crossword: Crossword| {
+            for square in hidden.iter() {
+                crossword.contents[*square] = ' ';
+            }
+            crossword
+        };
+
+        let result = match self.search_first(
+            &crossword,
+            &word_boundaries,
+            &word_boundary_lookup,
+            start_time,
+            &|| false,
+        ) {
+            Ok(solution) => {
+                self.record_themer_answers(&solution);
+                Ok(reveal(solution))
+            }
+            Err(FillError::Timeout {
+                best_partial,
+                filled_fraction,
+            }) => Err(FillError::Timeout {
+                best_partial: reveal(best_partial),
+                filled_fraction,
+            }),
+            Err(e) => Err(e),
+        };
+        self.stats.elapsed = start_time.elapsed();
+        result
+    }
+
+    /// The configuration of the fillers searching on other threads, see
+    /// `WorkerSettings::worker`.
+    fn worker_settings(&self) -> WorkerSettings<'s> {
+        WorkerSettings {
+            trie: self.trie,
+            random: self.random,
+            max_time_seconds: self.max_time_seconds,
+            heuristic: self.heuristic,
+            start_slot_offset: self.start_slot_offset,
+            scorer: self.scorer.clone(),
+            cost_fn: self.cost_fn.clone(),
+            word_scores: self.word_scores,
+            letter_diversity: self.letter_diversity,
+            forbid_reversed: self.forbid_reversed,
+            max_candidates_per_slot: self.max_candidates_per_slot,
+            prune_below_candidates: self.prune_below_candidates,
+            max_duplicate: self.max_duplicate,
+            blocklist: self.blocklist.clone(),
+            required_words: self.required_words.clone(),
+            block_chars: self.block_chars.clone(),
+            themers: self.themers.clone(),
+        }
+    }
+
+    /// Searches depth first from `initial_crossword`, passing every complete solution to
+    /// `on_solution` until it returns false. Returns Ok(true) if `on_solution` ended the
+    /// search, and Ok(false) if the candidates ran out or `should_stop`, polled for every
+    /// candidate, returned true.
+    fn search(
+        &mut self,
+        initial_crossword: &Crossword,
+        word_boundaries: &[WordBoundary],
+        word_boundary_lookup: &FxHashMap<(Direction, usize, usize), &WordBoundary>,
+        start_time: Instant,
+        should_stop: &dyn Fn() -> bool,
+        on_solution: &mut dyn FnMut(Crossword) -> bool,
+    ) -> Result<bool, FillError> {
+        let mut candidate_count = 0;
+        let mut best_partial = initial_crossword.clone();
+        let mut best_open_squares = open_squares(&best_partial);
+
+        // candidates with their depth and the words completed on their branch, and the
+        // slots filled on the way to the current candidate when tracing
+        let committed = self.committed_words(initial_crossword, word_boundaries);
+        let mut candidates = vec![(initial_crossword.to_owned(), 0, committed)];
+        let mut path: Vec<WordBoundary> = vec![];
+
+        while let Some((candidate, depth, committed)) = candidates.pop() {
+            candidate_count += 1;
+            self.stats.candidate_count += 1;
+            let candidate_open_squares = open_squares(&candidate);
+
+            // every candidate but the input grid was checked as viable when it was pushed
+            if candidate_count > 1 && candidate_open_squares == 0 {
+                if self.tracing {
+                    self.trace = self.trace_steps(
+                        initial_crossword,
+                        &candidate,
+                        word_boundaries,
+                        &path[..depth],
+                    );
+                }
+                if !on_solution(candidate) {
+                    return Ok(true);
+                }
+                continue;
+            }
+
+            let elapsed_secs = start_time.elapsed().as_secs();
+            if elapsed_secs > self.max_time_seconds {
+                return Err(Filler::timeout(best_partial));
+            }
+
+            if should_stop() {
+                return Ok(false);
+            }
+
+            if candidate_open_squares < best_open_squares {
+                best_open_squares = candidate_open_squares;
+                best_partial = candidate.clone();
+            }
+
+            self.report_progress(candidate_count, start_time, &candidate);
+
+            let (slot, new_candidates) = self.expand(
+                &candidate,
+                &committed,
+                word_boundaries,
+                word_boundary_lookup,
+            )?;
+            if self.tracing {
+                path.truncate(depth);
+                path.push(slot);
+            }
+            candidates.extend(
+                new_candidates
+                    .into_iter()
+                    .map(|(c, committed)| (c, depth + 1, committed)),
+            );
+        }
+
+        Ok(false)
+    }
+
+    /// Searches for the first solution from `initial_crossword`, see `search`.
+    fn search_first(
+        &mut self,
+        initial_crossword: &Crossword,
+        word_boundaries: &[WordBoundary],
+        word_boundary_lookup: &FxHashMap<(Direction, usize, usize), &WordBoundary>,
+        start_time: Instant,
+        should_stop: &dyn Fn() -> bool,
+    ) -> Result<Crossword, FillError> {
+        let mut solution = None;
+        self.search(
+            initial_crossword,
+            word_boundaries,
+            word_boundary_lookup,
+            start_time,
+            should_stop,
+            &mut |candidate| {
+                solution = Some(candidate);
+                false
+            },
+        )?;
+        solution.ok_or(FillError::NoSolution)
+    }
+
+    /// Fills the slot picked by the heuristic with every viable word, returning the new
+    /// candidates, each with the words completed on its branch, ordered so that the
+    /// preferred word, or the cheapest grid with a cost function, comes last. `committed`
+    /// holds the words completed on the branch of `candidate`.
+    fn expand(
+        &mut self,
+        candidate: &Crossword,
+        committed: &CommittedWords,
+        word_boundaries: &[WordBoundary],
+        word_boundary_lookup: &FxHashMap<(Direction, usize, usize), &WordBoundary>,
+    ) -> Result<(WordBoundary, Vec<(Crossword, CommittedWords)>), FillError> {
+        let to_fill = self
+            .select_slot(candidate, word_boundaries)
+            .ok_or_else(|| FillError::InvalidGrid("No fillable words found".to_string()))?;
+
+        let orthogonals = words_orthogonal_to_word(to_fill.word_boundary, word_boundary_lookup);
+        let is_themer = self.themers.contains(to_fill.word_boundary);
+
+        let allowed_letters = self.allowed_letters(candidate, &to_fill, &orthogonals);
+        let mut potential_fills = self
+            .trie
+            .words_with_letters(to_fill.clone(), &allowed_letters);
+        potential_fills.retain(|word| !self.blocklist.contains(word));
+        self.order_fills(&mut potential_fills, candidate, to_fill.word_boundary);
+
+        // the slot is checked with its crossings, against the words completed on the
+        // way to the candidate in the other slots
+        let mut checked = orthogonals.clone();
+        checked.push(to_fill.word_boundary);
+        let checked_slots: Vec<usize> = checked
+            .iter()
+            .map(|checked| {
+                word_boundaries
+                    .iter()
+                    .position(|wb| wb == *checked)
+                    .unwrap()
+            })
+            .collect();
+        let mut committed_counts = FxHashMap::default();
+        let mut already_complete = vec![false; checked.len()];
+        for (slot, key) in committed.iter() {
+            match checked_slots.iter().position(|checked| *checked == slot) {
+                Some(index) => already_complete[index] = true,
+                None => *committed_counts.entry(key).or_insert(0) += 1,
+            }
+        }
+
+        // candidates are explored last in, first out, so `potential_fills` is ordered
+        // with the preferred words last; a themer only keeps its preferred viable word
+        let mut new_candidates = vec![];
+        for potential_fill in potential_fills {
+            let new_candidate = fill_one_word(candidate, &to_fill.clone(), &potential_fill);
+
+            let viable = is_viable_reuse(
+                &new_candidate,
+                &checked,
+                self.trie,
+                &committed_counts,
+                self.max_duplicate,
+                &mut self.is_viable_cache,
+            ) && !(self.forbid_reversed
+                && Filler::has_reversed_pair(&new_candidate, word_boundaries.iter()))
+                && !self.has_blocked_word(&new_candidate, orthogonals.iter().copied())
+                && self.can_place_required_words(&new_candidate, word_boundaries)
+                && !self.has_few_candidates(&new_candidate, &orthogonals);
+            if viable {
+                if is_themer {
+                    new_candidates.clear();
+                }
+                let mut new_committed = committed.clone();
+                if self.max_duplicate > 0 {
+                    for (index, word_boundary) in checked.iter().enumerate() {
+                        let (key, full) =
+                            word_key(WordIterator::new(&new_candidate, word_boundary));
+                        if full && !already_complete[index] {
+                            new_committed = new_committed.with(checked_slots[index], key);
+                        }
+                    }
+                }
+                new_candidates.push((new_candidate, new_committed));
+            }
+        }
+        if let Some(cost_fn) = &self.cost_fn {
+            // the cheapest grid goes last, to be explored next
+            new_candidates.sort_by_cached_key(|(candidate, _)| Reverse(cost_fn(candidate)));
+        }
+        Ok((to_fill.word_boundary.clone(), new_candidates))
+    }
+
+    /// The letters the crossing slot of each open square of `to_fill` accepts in that
+    /// square, or None for squares that are already filled or not crossed. A word placed
+    /// in `to_fill` only changes one square of each crossing slot, so the slot stays
+    /// viable exactly when the word's letter is one of these.
+    fn allowed_letters(
+        &mut self,
+        candidate: &Crossword,
+        to_fill: &WordIterator,
+        orthogonals: &[&WordBoundary],
+    ) -> Vec<Option<FxHashSet<char>>> {
+        to_fill
+            .clone()
+            .zip(to_fill.word_boundary.squares())
+            .map(|(c, (row, col))| {
+                if c != ' ' {
+                    return None;
+                }
+                let crossing = orthogonals
+                    .iter()
+                    .find(|wb| wb.squares().any(|square| square == (row, col)))?;
+                let position = (row - crossing.start_row) + (col - crossing.start_col);
+                let letters = self.letters_cache.letters(
+                    WordIterator::new(candidate, crossing),
+                    position,
+                    self.trie,
+                    &mut self.word_cache,
+                );
+                Some(letters.clone())
+            })
+            .collect()
+    }
+
+    /// The complete words of `crossword`, to start a search branch from it. Words are
+    /// only tracked to limit duplicates, so none are without a limit.
+    fn committed_words(
+        &self,
+        crossword: &Crossword,
+        word_boundaries: &[WordBoundary],
+    ) -> CommittedWords {
+        if self.max_duplicate == 0 {
+            return CommittedWords::default();
+        }
+        CommittedWords::new(crossword, word_boundaries)
+    }
+
+    /// Reports the progress to the `on_progress` callback for the first candidate and
+    /// every `PROGRESS_INTERVAL` candidates after it.
+    fn report_progress(&mut self, candidates: usize, start_time: Instant, current: &Crossword) {
+        if !(candidates - 1).is_multiple_of(PROGRESS_INTERVAL) {
+            return;
+        }
+        if let Some(progress) = self.progress.as_mut() {
+            progress(Progress {
+                candidates,
+                elapsed_ms: start_time.elapsed().as_millis() as u64,
+                current: current.clone(),
+            });
+        }
+    }
+
+    /// Writes the progress of a logging filler to stderr.
+    fn log_progress(progress: Progress) {
+        eprintln!("[INFO] Current candidate:\n{}", progress.current);
+        eprintln!(
+            "[INFO] Throughput: {} candidates/ms, total {} candidates, time taken: {} seconds",
+            progress.candidates as f32 / progress.elapsed_ms.max(1) as f32,
+            progress.candidates,
+            progress.elapsed_ms / 1000,
+        );
+    }
+
+    /// Whether an open slot of `slots` has fewer words than `prune_below_candidates`.
+    fn has_few_candidates(&mut self, crossword: &Crossword, slots: &[&WordBoundary]) -> bool {
+        let min_candidates = match self.prune_below_candidates {
+            Some(min_candidates) => min_candidates,
+            None => return false,
+        };
+        slots
+            .iter()
+            .map(|slot| WordIterator::new(crossword, slot))
+            .filter(|iter| iter.clone().any(|c| c == ' '))
+            .any(|iter| self.word_cache.words(iter, self.trie).len() < min_candidates)
+    }
+
+    /// The trace of `solution`, reached from `initial` by filling the slots of `path` in
+    /// order, see `Filler::trace`.
+    fn trace_steps(
+        &self,
+        initial: &Crossword,
+        solution: &Crossword,
+        word_boundaries: &[WordBoundary],
+        path: &[WordBoundary],
+    ) -> Vec<TraceStep> {
+        let mut starts: Vec<(usize, usize)> =
+            parse_word_boundaries_with(solution, &self.block_chars)
+                .iter()
+                .map(|wb| (wb.start_row, wb.start_col))
+                .collect();
+        starts.sort_unstable();
+        starts.dedup();
+        let number = |wb: &WordBoundary| {
+            starts
+                .binary_search(&(wb.start_row, wb.start_col))
+                .map_or(0, |index| index + 1)
+        };
+        let mut slots: Vec<&WordBoundary> = word_boundaries.iter().collect();
+        slots.sort_by_key(|wb| (number(wb), wb.direction == Direction::Down));
+
+        let index = |(row, col): (usize, usize)| row * solution.width + col;
+        let mut filled: Vec<bool> = initial.contents.iter().map(|c| *c != ' ').collect();
+        let mut completed: Vec<bool> = slots
+            .iter()
+            .map(|wb| wb.squares().all(|square| filled[index(square)]))
+            .collect();
+        let mut steps = vec![];
+        let step = |wb: &WordBoundary| TraceStep {
+            number: number(wb),
+            direction: wb.direction.clone(),
+            word: pattern_string(solution, wb, ' '),
+        };
+        for slot in path {
+            for square in slot.squares() {
+                filled[index(square)] = true;
+            }
+            if let Some(position) = slots.iter().position(|wb| *wb == slot) {
+                completed[position] = true;
+            }
+            steps.push(step(slot));
+            for (position, wb) in slots.iter().enumerate() {
+                if !completed[position] && wb.squares().all(|square| filled[index(square)]) {
+                    completed[position] = true;
+                    steps.push(step(wb));
+                }
+            }
+        }
+        steps
+    }
+
+    fn timeout(best_partial: Crossword) -> FillError {
+        let letter_squares = best_partial
+            .contents
+            .iter()
+            .filter(|c| !BLACK_SQUARE.contains(c) && **c != VOID_SQUARE)
+            .count();
+        let filled_fraction = if letter_squares == 0 {
+            1.0
+        } else {
+            1.0 - open_squares(&best_partial) as f64 / letter_squares as f64
+        };
+        FillError::Timeout {
+            best_partial,
+            filled_fraction,
+        }
+    }
+
+    /// Whether some complete word of the grid appears reversed in another slot.
+    /// Palindromes don't count on their own.
+    fn has_reversed_pair<'w>(
+        crossword: &Crossword,
+        word_boundaries: impl Iterator<Item = &'w WordBoundary>,
+    ) -> bool {
+        let words: FxHashSet<String> = word_boundaries
+            .map(|wb| pattern_string(crossword, wb, ' '))
+            .filter(|word| !word.contains(' '))
+            .collect();
+        words.iter().any(|word| {
+            let reversed: String = word.chars().rev().collect();
+            reversed != *word && words.contains(&reversed)
+        })
+    }
+
+    /// Counts the letters in the slots crossing `slot`, leaving out the squares of `slot`.
+    fn crossing_letters(&self, crossword: &Crossword, slot: &WordBoundary) -> FxHashMap<char, i32> {
+        let mut counts = FxHashMap::default();
+        let (row_step, col_step) = match slot.direction {
+            Direction::Across => (1, 0),
+            Direction::Down => (0, 1),
+        };
+        for (row, col) in slot.squares() {
+            for &sign in [-1isize, 1].iter() {
+                let (mut row, mut col) = (row as isize, col as isize);
+                loop {
+                    row += sign * row_step;
+                    col += sign * col_step;
+                    if row < 0
+                        || col < 0
+                        || row as usize >= crossword.height
+                        || col as usize >= crossword.width
+                    {
+                        break;
+                    }
+                    let c = crossword.contents[row as usize * crossword.width + col as usize];
+                    if self.block_chars.contains(c) {
+                        break;
+                    }
+                    if c != ' ' {
+                        *counts.entry(c).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    /// The number of distinct letters of `word` minus the number of its letters already in
+    /// the crossing slots, counted in `crossings`. 0 without crossings counted.
+    fn letter_diversity(word: &str, crossings: &FxHashMap<char, i32>) -> i32 {
+        if crossings.is_empty() {
+            return 0;
+        }
+        let distinct: FxHashSet<char> = word.chars().collect();
+        let reused: i32 = word.chars().filter_map(|c| crossings.get(&c)).sum();
+        distinct.len() as i32 - reused
+    }
+
+    /// Whether some complete word of the grid among `word_boundaries` is blocklisted.
+    fn has_blocked_word<'w>(
+        &self,
+        crossword: &Crossword,
+        mut word_boundaries: impl Iterator<Item = &'w WordBoundary>,
+    ) -> bool {
+        !self.blocklist.is_empty()
+            && word_boundaries.any(|wb| {
+                let word = pattern_string(crossword, wb, ' ');
+                !word.contains(' ') && self.blocklist.contains(&word)
+            })
+    }
+
+    /// Whether every required word is in some slot of the grid or still fits the letters
+    /// of some slot of its length.
+    fn can_place_required_words(
+        &self,
+        crossword: &Crossword,
+        word_boundaries: &[WordBoundary],
+    ) -> bool {
+        self.required_words.iter().all(|word| {
+            word_boundaries.iter().any(|wb| {
+                wb.length == word.chars().count()
+                    && WordIterator::new(crossword, wb)
+                        .zip(word.chars())
+                        .all(|(square, c)| square == ' ' || square == c)
+            })
+        })
+    }
+}
+
+/// The number of squares of the grid left to fill.
+fn open_squares(crossword: &Crossword) -> usize {
+    crossword.contents.iter().filter(|c| **c == ' ').count()
+}
+
+impl<'s> Fill for Filler<'s> {
+    fn fill(&mut self, initial_crossword: &Crossword) -> Result<Crossword, FillError> {
+        let start_time = Instant::now();
+        self.stats = FillStats::default();
+        let result = self.fill_from(initial_crossword, start_time);
+        self.stats.elapsed = start_time.elapsed();
+        result
+    }
+}
+
+impl<'s> Filler<'s> {
+    fn fill_from(
+        &mut self,
+        initial_crossword: &Crossword,
+        start_time: Instant,
+    ) -> Result<Crossword, FillError> {
+        self.themer_answers.clear();
+
+        let initial_crossword = &self.prepare(initial_crossword)?;
+
+        let word_boundaries = parse_word_boundaries_with(initial_crossword, &self.block_chars);
+        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+
+        let solution = self.search_first(
+            initial_crossword,
+            &word_boundaries,
+            &word_boundary_lookup,
+            start_time,
+            &|| false,
+        )?;
+        self.record_themer_answers(&solution);
+        Ok(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        crossword::{Direction, WordIterator},
+        fill::{
+            fill_one_word, Fill, FillError, FillStats, FillerState, Progress, RejectionReason,
+            SlotHeuristic,
+        },
+        parse::{BlockChars, WordBoundary},
+        Trie,
+    };
+
+    use crate::Crossword;
+
+    use std::{
+        cell::RefCell, cmp::Ordering, collections::HashSet, rc::Rc, sync::mpsc, time::Instant,
+    };
+
+    use super::Filler;
+
+    #[test]
+    fn test() {
+        assert_eq!((1, 2).cmp(&(3, 4)), Ordering::Less)
+    }
+
+    #[test]
+    fn medium_grid() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
+
+        let now = Instant::now();
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, true);
+        let filled_puz = filler.fill(&grid).unwrap();
+        println!("Filled in {} seconds.", now.elapsed().as_secs());
+        println!("{}", filled_puz);
+    }
+
+    #[test]
+    fn medium_grid_cost_fn() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
+
+        let mut depth_first = Filler::new(&trie, false, 60, false);
+        depth_first.fill(&grid).unwrap();
+        let mut best_first = Filler::new(&trie, false, 60, false);
+        best_first.with_cost_fn(Filler::viable_words_cost(&trie));
+        best_first.fill(&grid).unwrap();
+
+        println!(
+            "depth first: {}, best first: {}",
+            depth_first.stats().candidate_count,
+            best_first.stats().candidate_count
+        );
+        assert!(best_first.stats().candidate_count < depth_first.stats().candidate_count);
+    }
+
+    #[test]
+    fn medium_grid_ro() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
+
+        let now = Instant::now();
+        let trie = Trie::load("ro_dex_080").expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, true, 60, true);
+        let filled_puz = filler.fill(&grid).unwrap();
+        println!("Filled in {} seconds.", now.elapsed().as_secs());
+        println!("{}", filled_puz);
+    }
+
+    #[test]
+    fn open_grid() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXXX
+XXXXX
+XXXXX
+XXXXX
+XXXXX
+",
+        ))
+        .unwrap();
+
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.fill(&grid).unwrap();
+        let generic_candidates = filler.stats().candidate_count;
+
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_heuristic(SlotHeuristic::AcrossFirst);
+        let filled_puz = filler.fill(&grid).unwrap();
+        assert!(filler.stats().candidate_count < generic_candidates);
+
+        assert!(!filled_puz.contents.contains(&' '));
+        let mut words = filled_puz.words(Direction::Across);
+        words.extend(filled_puz.words(Direction::Down));
+        assert_eq!(10, words.len());
+        for word in words.iter() {
+            assert!(trie.is_viable(word.chars()));
+        }
+        words.sort();
+        words.dedup();
+        assert_eq!(10, words.len());
+
+        // the rows are filled top to bottom, completing the columns with the last one, and
+        // tracing doesn't change the solution
+        filler.with_trace(true);
+        assert_eq!(filled_puz, filler.fill(&grid).unwrap());
+        let slots: Vec<(usize, Direction)> = filler
+            .trace()
+            .iter()
+            .map(|step| (step.number, step.direction.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (1, Direction::Across),
+                (6, Direction::Across),
+                (7, Direction::Across),
+                (8, Direction::Across),
+                (9, Direction::Across),
+                (1, Direction::Down),
+                (2, Direction::Down),
+                (3, Direction::Down),
+                (4, Direction::Down),
+                (5, Direction::Down),
+            ],
+            slots
+        );
+    }
+
+    #[test]
+    fn medium_grid_minimum_entropy() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
+
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, true);
+        filler.with_heuristic(SlotHeuristic::MinimumEntropy);
+        let filled_puz = filler.fill(&grid).unwrap();
+        println!("{}", filled_puz);
+
+        assert!(!filled_puz.contents.contains(&' '));
+    }
+
+    #[test]
+    fn medium_grid_longest_first() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
+
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler
+            .with_heuristic(SlotHeuristic::LongestFirst)
+            .with_trace(true);
+        let filled_puz = filler.fill(&grid).unwrap();
+
+        assert!(!filled_puz.contents.contains(&' '));
+        assert_eq!(7, filler.trace()[0].word.len());
+    }
+
+    /// Fills the sample grids with every heuristic from a fixed seed and prints a table
+    /// of the outcomes and candidate counts, to compare heuristics. Run with
+    /// `cargo test --release compare_heuristics -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn compare_heuristics() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let grids = [
+            "20201005_empty",
+            "20201012_empty",
+            "20201107_empty",
+            "20201124_empty",
+            "20201128_empty",
+            "code_project",
+            "waffle",
+        ];
+
+        println!(
+            "{:<16} {:<18} {:<12} {:>12} {:>10}",
+            "grid", "heuristic", "result", "candidates", "ms"
+        );
+        for name in grids.iter() {
+            let grid = Crossword::parse_from_file(format!("grids/{}.txt", name)).unwrap();
+            for heuristic in SlotHeuristic::ALL.iter() {
+                let mut filler = Filler::new(&trie, true, 30, false);
+                filler.with_heuristic(*heuristic);
+                filler.with_seed(42);
+                let result = match filler.fill(&grid) {
+                    Ok(_) => "ok",
+                    Err(FillError::Timeout { .. }) => "timeout",
+                    Err(FillError::NoSolution) => "no solution",
+                    Err(_) => "invalid",
+                };
+                let stats = filler.stats();
+                println!(
+                    "{:<16} {:<18} {:<12} {:>12} {:>10}",
+                    name,
+                    format!("{:?}", heuristic),
+                    result,
+                    stats.candidate_count,
+                    stats.elapsed.as_millis()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn scorer() {
+        let grid = Crossword::parse(String::from("XXX")).unwrap();
+        let trie = Trie::build(vec![
+            String::from("CAT"),
+            String::from("DOG"),
+            String::from("ZOO"),
+        ]);
+
+        let mut filler = Filler::new(&trie, true, 60, false);
+        filler.with_scorer(Box::new(|word, _| word.matches('Z').count() as i32));
+        assert_eq!("ZOO", filler.fill(&grid).unwrap().to_string());
+
+        let mut filler = Filler::new(&trie, true, 60, false);
+        filler.with_scorer(Box::new(|word, _| word.matches('D').count() as i32));
+        assert_eq!("DOG", filler.fill(&grid).unwrap().to_string());
+    }
+
+    #[test]
+    fn lowercase_letters_are_given() {
+        let grid = Crossword::parse(String::from("dXX")).unwrap();
+        let trie = Trie::build(vec![String::from("CAT"), String::from("DOG")]);
+
+        let mut filler = Filler::new(&trie, true, 60, false);
+        assert_eq!("DOG", filler.fill(&grid).unwrap().to_string());
+    }
+
+    #[test]
+    fn rebus_grids_are_rejected() {
+        let grid = Crossword::parse(String::from("C[AR]T\nXXX\nXXX")).unwrap();
+        let trie = Trie::build(
+            ["CART", "CAT", "AREA", "TEE", "AEE", "TAE"]
+                .iter()
+                .map(|word| word.to_string())
+                .collect(),
+        );
+
+        let mut filler = Filler::new(&trie, true, 60, false);
+        assert_eq!(
+            Err(FillError::InvalidGrid(String::from(
+                "Rebus square at row 0, column 1 cannot be filled"
+            ))),
+            filler.fill(&grid)
+        );
+    }
+
+    #[test]
+    fn word_scores() {
+        let grid = Crossword::parse(String::from("XXX")).unwrap();
+        let trie = Trie::build_scored(vec![(String::from("CAT"), 10), (String::from("DOG"), 20)]);
+
+        let mut filler = Filler::new(&trie, true, 60, false);
+        filler.with_word_scores(true);
+        assert_eq!("DOG", filler.fill(&grid).unwrap().to_string());
+        // the input grid, then DOG tried first
+        assert_eq!(2, filler.stats().candidate_count);
+
+        let trie = Trie::build_scored(vec![(String::from("CAT"), 30), (String::from("DOG"), 20)]);
+        let mut filler = Filler::new(&trie, true, 60, false);
+        filler.with_word_scores(true);
+        assert_eq!("CAT", filler.fill(&grid).unwrap().to_string());
+    }
+
+    #[test]
+    fn letter_diversity() {
+        let grid = Crossword::parse(String::from("XXX\n.A.\n.A.")).unwrap();
+        // BAA is placed down first, then ABA reuses both A of BAA and EBB none
+        let trie = Trie::build(vec![
+            String::from("BAA"),
+            String::from("ABA"),
+            String::from("EBB"),
+        ]);
+
+        for _ in 0..5 {
+            let mut filler = Filler::new(&trie, true, 60, false);
+            filler.with_letter_diversity(true);
+            assert_eq!("EBB\n.A.\n.A.", filler.fill(&grid).unwrap().to_string());
+        }
+
+        // a scorer takes precedence
+        let mut filler = Filler::new(&trie, true, 60, false);
+        filler
+            .with_letter_diversity(true)
+            .with_scorer(Box::new(|word, _| i32::from(word == "ABA")));
+        assert_eq!("ABA\n.A.\n.A.", filler.fill(&grid).unwrap().to_string());
+    }
+
+    #[test]
+    fn strict_mode() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...ZQXX
+",
+        ))
+        .unwrap();
+
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_strict(true);
+        let now = Instant::now();
+        let result = filler.fill(&grid);
+
+        assert_eq!(
+            Err(FillError::InvalidGrid(String::from(
+                "No word matches the given letters of 13 Across: `ZQ__`"
+            ))),
+            result
+        );
+        assert!(now.elapsed().as_secs() < 5);
+        let open = Crossword::parse(String::from("XXXX\nXXXX")).unwrap();
+        assert!(filler.validate_givens(&open).is_ok());
+    }
+
+    #[test]
+    fn themers() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
+        let seeded = WordBoundary::new(0, 0, 4, Direction::Across);
+        let grid = fill_one_word(&grid, &WordIterator::new(&grid, &seeded), "CATS");
+        let themer = WordBoundary::new(3, 0, 7, Direction::Across);
+
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, true);
+        filler.with_themers(vec![seeded.clone(), themer.clone()]);
+        let filled_puz = filler.fill(&grid).unwrap();
+        println!("{}", filled_puz);
+
+        let answers = filler.themer_answers();
+        assert_eq!(2, answers.len());
+        assert_eq!((seeded.clone(), String::from("CATS")), answers[0]);
+        assert_eq!(themer, answers[1].0);
+        assert_eq!(
+            answers[1].1,
+            WordIterator::new(&filled_puz, &themer).collect::<String>()
+        );
+        assert!(trie.is_viable(answers[1].1.chars()));
+        assert_eq!(
+            "CATS",
+            WordIterator::new(&filled_puz, &seeded).collect::<String>()
+        );
+    }
+
+    #[test]
+    fn answers() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
+
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_answers(vec![
+            (1, Direction::Across, String::from("cats")),
+            (1, Direction::Down, String::from("CLAW")),
+            (7, Direction::Across, String::from("WAXWING")),
+        ]);
+        let filled_puz = filler.fill(&grid).unwrap();
+        println!("{}", filled_puz);
+
+        assert!(!filled_puz.contents.contains(&' '));
+        let entries = filled_puz.numbered_entries();
+        let answer = |number, direction| {
+            entries
+                .iter()
+                .find(|e| e.number == number && e.word_boundary.direction == direction)
+                .map(|e| e.answer.clone())
+                .unwrap()
+        };
+        assert_eq!("CATS", answer(1, Direction::Across));
+        assert_eq!("CLAW", answer(1, Direction::Down));
+        assert_eq!("WAXWING", answer(7, Direction::Across));
+
+        filler.with_answers(vec![(1, Direction::Down, String::from("DOGS"))]);
+        assert!(matches!(
+            filler.fill(&filled_puz),
+            Err(FillError::InvalidGrid(_))
+        ));
+        filler.with_answers(vec![(42, Direction::Across, String::from("CATS"))]);
+        assert!(matches!(filler.fill(&grid), Err(FillError::InvalidGrid(_))));
+        filler.with_answers(vec![(1, Direction::Across, String::from("CAT"))]);
+        assert!(matches!(filler.fill(&grid), Err(FillError::InvalidGrid(_))));
+    }
+
+    #[test]
+    fn start_slot_offset() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let fill_with_offset = |offset| {
+            let mut filler = Filler::new(&trie, false, 60, false);
+            filler.with_start_slot_offset(offset);
+            filler.fill(&grid).unwrap()
+        };
+
+        let offsets = [0, 12, 24, 36];
+        let solutions: Vec<Crossword> = offsets.iter().map(|o| fill_with_offset(*o)).collect();
+        let distinct: HashSet<&Crossword> = solutions.iter().collect();
+
+        assert!(distinct.len() > 1);
+        for (offset, solution) in offsets.iter().zip(solutions.iter()) {
+            assert_eq!(*solution, fill_with_offset(*offset));
+        }
+        assert_eq!(solutions[0], fill_with_offset(grid.width * grid.height));
+    }
+
+    #[test]
+    fn forbid_reversed() {
+        let grid = Crossword::parse(String::from("XXX.XXX")).unwrap();
+        let trie = Trie::build(vec![
+            String::from("BAT"),
+            String::from("TAB"),
+            String::from("CAT"),
+        ]);
+        // prefer BAT, then TAB, then CAT, and words not yet in the grid
+        let preference = |word: &str, grid: &Crossword| {
+            if grid.to_string().contains(word) {
+                return -1;
+            }
+            match word {
+                "BAT" => 2,
+                "TAB" => 1,
+                _ => 0,
+            }
+        };
+
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_scorer(Box::new(preference));
+        assert_eq!("BAT.TAB", filler.fill(&grid).unwrap().to_string());
+
+        filler.with_forbid_reversed(true);
+        assert_eq!("BAT.CAT", filler.fill(&grid).unwrap().to_string());
+
+        let open = Crossword::parse(String::from("XX\nXX")).unwrap();
+        let trie = Trie::build(
+            ["TO", "IT", "TI", "OT", "AN", "DO", "AD", "NO"]
+                .iter()
+                .map(|word| word.to_string())
+                .collect(),
+        );
+        let preference = |word: &str, _: &Crossword| match word {
+            "TO" => 2,
+            "IT" => 1,
+            _ => 0,
+        };
+
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_scorer(Box::new(preference));
+        assert_eq!("TO\nIT", filler.fill(&open).unwrap().to_string());
+
+        filler.with_forbid_reversed(true);
+        let filled = filler.fill(&open).unwrap();
+        let words: Vec<String> = filled
+            .words(Direction::Across)
+            .into_iter()
+            .chain(filled.words(Direction::Down))
+            .collect();
+        for word in &words {
+            assert!(!words.contains(&word.chars().rev().collect()));
+        }
+    }
+
+    #[test]
+    fn fill_direction() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX.
+XXXXX
+.XXXX
+XX.XX
+",
+        ))
+        .unwrap();
+
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, false);
+        let filled = filler.fill_direction(&grid, Direction::Across).unwrap();
+        println!("{}", filled);
+
+        for word in filled.words(Direction::Across) {
+            assert!(trie.is_viable(word.chars()), "{} is not a word", word);
+        }
+        // every square is in an across slot here, so the grid is full
+        assert_eq!(grid.clear_letters(), filled.clear_letters());
+        assert!(filled.contents.iter().all(|c| *c != ' '));
+
+        let grid = Crossword::parse(String::from(
+            "
+XXX
+X.X
+XXX
+",
+        ))
+        .unwrap();
+        let filled = filler.fill_direction(&grid, Direction::Across).unwrap();
+        println!("{}", filled);
+        assert_eq!("X.X", filled.to_string().lines().nth(1).unwrap());
+        assert!(!filled.to_string().lines().next().unwrap().contains('X'));
+    }
+
+    #[test]
+    fn empty_dictionary() {
+        let grid = Crossword::parse(String::from(
+            "
+XXX
+X.X
+XXX
+",
+        ))
+        .unwrap();
+        let trie = Trie::build(vec![]);
+        let mut filler = Filler::new(&trie, false, 60, false);
+
+        assert_eq!(Err(FillError::EmptyDictionary), filler.fill(&grid));
+        assert_eq!(
+            Err(FillError::EmptyDictionary),
+            filler.fill_direction(&grid, Direction::Down)
+        );
+    }
+
+    #[test]
+    fn has_unique_solution() {
+        let trie = Trie::build(vec![
+            String::from("AB"),
+            String::from("CD"),
+            String::from("AC"),
+            String::from("BD"),
+        ]);
+        let mut filler = Filler::new(&trie, false, 60, false);
+
+        // the solution and its transpose
+        let open = Crossword::parse(String::from("XX\nXX")).unwrap();
+        assert_eq!(Ok(2), filler.count_solutions(&open, 10));
+        assert_eq!(Ok(1), filler.count_solutions(&open, 1));
+        assert!(!filler.has_unique_solution(&open));
+
+        let given = Crossword::parse(String::from("XB\nXX")).unwrap();
+        assert_eq!(Ok(1), filler.count_solutions(&given, 10));
+        assert!(filler.has_unique_solution(&given));
+
+        let unsolvable = Crossword::parse(String::from("XX\nXA")).unwrap();
+        assert_eq!(Ok(0), filler.count_solutions(&unsolvable, 10));
+        assert!(!filler.has_unique_solution(&unsolvable));
+    }
+
+    #[test]
+    fn blocklist() {
+        let trie = Trie::build(vec![
+            String::from("AB"),
+            String::from("CD"),
+            String::from("AC"),
+            String::from("BD"),
+            String::from("EF"),
+            String::from("GH"),
+            String::from("EG"),
+            String::from("FH"),
+        ]);
+        let blocklist: HashSet<String> = vec![String::from("ab")].into_iter().collect();
+
+        for grid in ["XX\nXX", "XX.\nXX.\n..."].iter() {
+            let grid = Crossword::parse(String::from(*grid)).unwrap();
+            for _ in 0..10 {
+                let mut filler = Filler::new(&trie, true, 60, false);
+                filler.with_blocklist(blocklist.clone());
+                let result = filler.fill(&grid).unwrap();
+                let mut words = result.words(Direction::Across);
+                words.extend(result.words(Direction::Down));
+                assert!(!words.contains(&String::from("AB")), "{}", result);
+            }
+        }
+
+        // the first across and down words start with A or E
+        let all: HashSet<String> = vec!["AB", "ac", "EF", "eg"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_blocklist(all);
+        let grid = Crossword::parse(String::from("XX.\nXX.\n...")).unwrap();
+        assert_eq!(Err(FillError::NoSolution), filler.fill(&grid));
+        let grid = Crossword::parse(String::from("XX\nXX")).unwrap();
+        assert_eq!(Err(FillError::NoSolution), filler.fill(&grid));
+    }
+
+    #[test]
+    fn max_duplicate() {
+        // every fill repeats each word
+        let grid = Crossword::parse(String::from("XX\nXX")).unwrap();
+        let trie = Trie::build(vec![String::from("AA")]);
+
+        let mut filler = Filler::new(&trie, false, 10, false);
+        assert_eq!(Err(FillError::NoSolution), filler.fill(&grid));
+        filler.with_max_duplicate(4);
+        assert_eq!("AA\nAA", filler.fill(&grid).unwrap().to_string());
+        filler.with_max_duplicate(0);
+        assert_eq!("AA\nAA", filler.fill(&grid).unwrap().to_string());
+
+        let trie = Trie::build(vec![String::from("AB"), String::from("BA")]);
+        let mut filler = Filler::new(&trie, false, 10, false);
+        filler.with_max_duplicate(2);
+        let filled = filler.fill(&grid).unwrap();
+        assert_eq!(
+            filled.words(Direction::Across),
+            filled.words(Direction::Down)
+        );
+    }
+
+    #[test]
+    fn no_duplicate_answers() {
+        let trie = Trie::build(vec![
+            String::from("AB"),
+            String::from("CD"),
+            String::from("AC"),
+            String::from("BD"),
+        ]);
+        // the two corners don't cross, and could only be filled alike
+        let grid = Crossword::parse(String::from("XX..\nXX..\n..XX\n..XX")).unwrap();
+        let mut filler = Filler::new(&trie, false, 10, false);
+        assert_eq!(Err(FillError::NoSolution), filler.fill(&grid));
+
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let grid = Crossword::parse(String::from(
+            "
+XXX.XXX
+XXX.XXX
+XXXXXXX
+..XXX..
+XXXXXXX
+XXX.XXX
+XXX.XXX
+",
+        ))
+        .unwrap();
+        let mut filler = Filler::new(&trie, true, 60, false);
+        for _ in 0..3 {
+            let filled = filler.fill(&grid).unwrap();
+            let mut answers = filled.words(Direction::Across);
+            answers.extend(filled.words(Direction::Down));
+            let distinct: HashSet<&String> = answers.iter().collect();
+            assert_eq!(answers.len(), distinct.len(), "{}", filled);
+        }
+    }
+
+    #[test]
+    fn resume_from_state() {
+        let trie = Trie::build(vec![
+            String::from("AB"),
+            String::from("CD"),
+            String::from("AC"),
+            String::from("BD"),
+            String::from("EF"),
+            String::from("GH"),
+            String::from("EG"),
+            String::from("FH"),
+        ]);
+        // every fill from A has AB or AC across
+        let blocklist: HashSet<String> = vec![String::from("ab"), String::from("AC")]
+            .into_iter()
+            .collect();
+        let mut filler = Filler::new(&trie, true, 30, false);
+        filler
+            .with_blocklist(blocklist)
+            .with_forbid_reversed(true)
+            .with_max_candidates_per_slot(10)
+            .with_block_chars(BlockChars::new(&['.']));
+        let grid = Crossword::parse(String::from("XF\nXX")).unwrap();
+
+        let json = serde_json::to_string(&filler.state(&grid)).unwrap();
+        let state: FillerState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(filler.state(&grid), state);
+        assert_eq!(
+            vec![String::from("AB"), String::from("AC")],
+            state.blocklist
+        );
+        assert_eq!(grid, state.crossword().unwrap());
+        let mut resumed = Filler::from_state(&trie, &state);
+        assert_eq!(state, resumed.state(&grid));
+        let result = resumed.fill(&state.crossword().unwrap()).unwrap();
+        assert_eq!("EF\nGH", result.to_string());
+    }
+
+    #[test]
+    fn required_words() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let grid = Crossword::parse(String::from("XXX\nXXX\nXXX")).unwrap();
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_required_words(vec![String::from("cat")]);
+
+        let result = filler.fill(&grid).unwrap();
+
+        let mut words = result.words(Direction::Across);
+        words.extend(result.words(Direction::Down));
+        assert!(words.contains(&String::from("CAT")), "{}", result);
+
+        filler.with_required_words(vec![String::from("CATS")]);
+        assert!(matches!(filler.fill(&grid), Err(FillError::InvalidGrid(_))));
+
+        // no word fits with the given letters
+        filler.with_required_words(vec![String::from("QQQ")]);
+        assert_eq!(Err(FillError::NoSolution), filler.fill(&grid));
+    }
+
+    #[test]
+    fn trace() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX.
+XXXXX
+.XXXX
+XX.XX
+",
+        ))
+        .unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.fill(&grid).unwrap();
+        assert!(filler.trace().is_empty());
 
-    trie: &'s Trie,
-    random: bool,
-    max_time_seconds: u64,
-    log: bool,
-}
+        filler.with_trace(true);
+        let filled = filler.fill(&grid).unwrap();
+        let trace = filler.trace();
 
-impl<'s> Filler<'s> {
-    pub fn new(trie: &'s Trie, random: bool, max_time_seconds: u64, log: bool) -> Filler<'s> {
-        Filler {
-            word_cache: CachedWords::default(),
-            is_viable_cache: CachedIsViable::default(),
-            trie,
-            random,
-            max_time_seconds,
-            log: log,
+        let entries = filled.numbered_entries();
+        assert_eq!(entries.len(), trace.len());
+        for entry in entries {
+            assert_eq!(
+                1,
+                trace
+                    .iter()
+                    .filter(|step| step.number == entry.number
+                        && step.direction == entry.word_boundary.direction
+                        && step.word == entry.answer)
+                    .count()
+            );
         }
+
+        // the given answer is not part of the trace
+        let given = Crossword::parse(String::from("CAT\nXXX\nXXX")).unwrap();
+        filler.fill(&given).unwrap();
+        assert_eq!(5, filler.trace().len());
+        assert!(filler
+            .trace()
+            .iter()
+            .all(|step| (step.number, &step.direction) != (1, &Direction::Across)));
     }
-}
 
-impl<'s> Fill for Filler<'s> {
-    fn fill(&mut self, initial_crossword: &Crossword) -> Result<Crossword, String> {
-        let start_time = Instant::now();
-        let mut candidate_count = 0;
+    #[test]
+    fn max_candidates_per_slot() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX.
+XXXXX
+.XXXX
+XX.XX
+",
+        ))
+        .unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
 
-        let word_boundaries = parse_word_boundaries(&initial_crossword);
-        let mut already_used = HashSet::with_capacity_and_hasher(
-            word_boundaries.len(),
-            BuildHasherDefault::<FxHasher>::default(),
-        );
-        let mut candidates = vec![initial_crossword.to_owned()];
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_max_candidates_per_slot(10);
+        let filled = filler.fill(&grid).unwrap();
+        assert!(!filled.contents.contains(&' '));
 
-        let word_boundary_lookup = build_square_word_boundary_lookup(&word_boundaries);
+        // with a single candidate per slot, the search gives up instead of backtracking
+        let trie = Trie::build(vec![
+            String::from("AB"),
+            String::from("CD"),
+            String::from("AC"),
+            String::from("BD"),
+        ]);
+        let grid = Crossword::parse(String::from("XX\nXX")).unwrap();
+        let mut filler = Filler::new(&trie, false, 60, false);
+        assert!(filler.fill(&grid).is_ok());
+        filler.with_max_candidates_per_slot(1);
+        assert_eq!(Err(FillError::NoSolution), filler.fill(&grid));
+    }
 
-        while let Some(candidate) = candidates.pop() {
-            candidate_count += 1;
+    #[test]
+    fn prune_below_candidates() {
+        // whether pruning saves candidates depends on the grid and the search order, and
+        // it does on this one
+        let grid = Crossword::parse(String::from(
+            "
+.XXXX
+XXXXX
+XXXXX
+XXXXX
+XXXX.
+",
+        ))
+        .unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
 
-            let elapsed_secs = start_time.elapsed().as_secs();
-            if elapsed_secs > self.max_time_seconds {
-                if self.log {
-                    eprintln!(
-                        "[INFO] Time limit of {} seconds reached after {} candidates",
-                        self.max_time_seconds, candidate_count
-                    );
-                }
-                return Err(format!(
-                    "Time limit of {} seconds reached after {} candidates",
-                    self.max_time_seconds, candidate_count
-                ));
-            }
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.fill(&grid).unwrap();
+        let unpruned = filler.stats().candidate_count;
+        filler.with_prune_below_candidates(3);
+        let filled = filler.fill(&grid).unwrap();
 
-            if self.log && (candidate_count % 10_000 == 0) {
-                eprintln!("[INFO] Current candidate:\n{}", candidate);
-                eprintln!(
-                    "[INFO] Throughput: {} candidates/ms, total {} candidates, time taken: {} seconds",
-                    candidate_count as f32 / start_time.elapsed().as_millis() as f32,
-                    candidate_count,
-                    start_time.elapsed().as_secs(),
-                );
-            }
+        assert!(!filled.contents.contains(&' '));
+        println!(
+            "candidates: {} unpruned, {} pruned",
+            unpruned,
+            filler.stats().candidate_count
+        );
+        assert!(filler.stats().candidate_count < unpruned);
+    }
 
-            let to_fill = word_boundaries
+    #[test]
+    fn word_cost() {
+        let grid = Crossword::parse(String::from(
+            "
+XXX
+X.X
+XXX
+",
+        ))
+        .unwrap();
+        let trie = Trie::build(
+            ["CAT", "CAR", "TAR", "RAT", "TOT"]
                 .iter()
-                .map(|word_boundary| WordIterator::new(&candidate, word_boundary))
-                .filter(|iter| iter.clone().any(|c| c == ' '))
-                .min_by_key(|iter| {
-                    let words = self.word_cache.words(iter.clone(), self.trie);
-                    (
-                        words.len(),
-                        iter.word_boundary.start_row,
-                        iter.word_boundary.start_col,
-                    )
-                })
-                .ok_or_else(|| "No fillable words found".to_string())?;
-
-            let orthogonals =
-                words_orthogonal_to_word(&to_fill.word_boundary, &word_boundary_lookup);
+                .map(|word| word.to_string())
+                .collect(),
+        );
+        let top = WordBoundary::new(0, 0, 3, Direction::Across);
 
-            let mut potential_fills = self.word_cache.words(to_fill.clone(), self.trie).to_vec();
+        let mut filler = Filler::new(&trie, false, 60, false);
+        assert_eq!(0, filler.word_cost(&grid, &top, "CAT"));
+        assert_eq!(1, filler.word_cost(&grid, &top, "CAB"));
+        assert_eq!(2, filler.word_cost(&grid, &top, "BAB"));
 
-            if self.random {
-                potential_fills.shuffle(&mut rand::rng());
-            }
+        // the middle square is open for a filler that only stops words at `.`
+        let grid = Crossword::parse(String::from("XXX\nX:X\nXXX")).unwrap();
+        let trie = Trie::build(
+            ["CAT", "COT", "ACT", "TAT"]
+                .iter()
+                .map(|word| word.to_string())
+                .collect(),
+        );
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_block_chars(BlockChars::new(&['.']));
+        assert_eq!(0, filler.word_cost(&grid, &top, "CAT"));
+        assert_eq!(None, filler.explain_rejection(&grid, &top, "CAT"));
+    }
 
-            for potential_fill in potential_fills {
-                let new_candidate = fill_one_word(&candidate, &to_fill.clone(), &potential_fill);
+    #[test]
+    fn fill_to_channel() {
+        let grid = Crossword::parse(String::from("XXX")).unwrap();
+        let trie = Trie::build(vec![
+            String::from("CAT"),
+            String::from("DOG"),
+            String::from("ZOO"),
+        ]);
+        let mut filler = Filler::new(&trie, false, 60, false);
 
-                let (viable, tmp) = is_viable_reuse(
-                    &new_candidate,
-                    &orthogonals,
-                    self.trie,
-                    already_used,
-                    &mut self.is_viable_cache,
-                );
-                already_used = tmp;
-                already_used.clear();
-
-                if viable {
-                    if !new_candidate.contents.contains(&' ') {
-                        if self.log {
-                            eprintln!(
-                                "[INFO] Found a complete solution after {} candidates, time taken: {} ms",
-                                candidate_count,
-                                start_time.elapsed().as_millis(),
-                            );
-                        }
-                        return Ok(new_candidate);
-                    }
-                    candidates.push(new_candidate);
-                }
-            }
-        }
+        let (tx, rx) = mpsc::channel();
+        assert_eq!(Ok(3), filler.fill_to_channel(&grid, tx));
+        let mut solutions: Vec<String> = rx.iter().map(|c| c.to_string()).collect();
+        solutions.sort();
+        assert_eq!(vec!["CAT", "DOG", "ZOO"], solutions);
 
-        Err("No valid solution found".to_string())
+        let (tx, rx) = mpsc::channel();
+        drop(rx);
+        assert_eq!(Ok(0), filler.fill_to_channel(&grid, tx));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{fill::Fill, Trie};
+    #[test]
+    fn generate_and_fill_works() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 10, false);
+        let filled_puz = filler.generate_and_fill(5, 5, 0.2, 7).unwrap();
+        println!("{}", filled_puz);
 
-    use crate::Crossword;
+        assert_eq!(5, filled_puz.width);
+        assert_eq!(5, filled_puz.height);
+        assert!(!filled_puz.contents.contains(&' '));
+    }
+
+    #[test]
+    fn fill_parallel() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+        ))
+        .unwrap();
 
-    use std::{cmp::Ordering, time::Instant};
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, true, 60, false);
+        for num_threads in [1, 4].iter() {
+            let result = filler.fill_parallel(&grid, *num_threads).unwrap();
 
-    use super::Filler;
+            assert!(!result.contents.contains(&' '));
+            for (square, given) in result.contents.iter().zip(grid.contents.iter()) {
+                assert!(*given == ' ' || given == square);
+            }
+            assert!(filler.stats().candidate_count > 1);
+        }
 
-    #[test]
-    fn test() {
-        assert_eq!((1, 2).cmp(&(3, 4)), Ordering::Less)
+        let unsolvable = Crossword::parse(String::from("QX\nXQ")).unwrap();
+        assert_eq!(
+            Err(FillError::NoSolution),
+            filler.fill_parallel(&unsolvable, 4)
+        );
     }
 
     #[test]
-    fn medium_grid() {
+    fn fill_parallel_deterministic_ignores_thread_count() {
         let grid = Crossword::parse(String::from(
             "
 XXXX...
@@ -170,17 +2755,19 @@ XXXXXXX
         ))
         .unwrap();
 
-        let now = Instant::now();
         let trie = Trie::load_default().expect("Failed to load trie");
-        let mut filler = Filler::new(&trie, false, 60, true);
-        let filled_puz = filler.fill(&grid).unwrap();
-        println!("Filled in {} seconds.", now.elapsed().as_secs());
-        println!("{}", filled_puz);
+        let mut filler = Filler::new(&trie, true, 60, false);
+        let one_thread = filler.fill_parallel_deterministic(&grid, 1, 42).unwrap();
+        let four_threads = filler.fill_parallel_deterministic(&grid, 4, 42).unwrap();
+
+        assert!(!one_thread.contents.contains(&' '));
+        assert_eq!(one_thread, four_threads);
     }
 
     #[test]
-    fn medium_grid_ro() {
-        let grid = Crossword::parse(String::from(
+    fn scattered_fixed_letters() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let grids = [
             "
 XXXX...
 XXXX...
@@ -189,16 +2776,223 @@ XXXXXXX
 ...XXXX
 ...XXXX
 ...XXXX
+",
+            "
+XXXX
+XXXX
+XXXX
+XXXX
+",
+        ];
+
+        for grid in grids.iter() {
+            let grid = Crossword::parse(String::from(*grid)).unwrap();
+            let mut filler = Filler::new(&trie, false, 60, false);
+            let solution = filler.fill(&grid).unwrap();
+
+            // keep the letters of a known solution on a checkerboard
+            let mut fixed = grid.clone();
+            for (index, c) in fixed.contents.iter_mut().enumerate() {
+                let (row, col) = (index / grid.width, index % grid.width);
+                if (row + col) % 2 == 0 {
+                    *c = solution.contents[index];
+                }
+            }
+
+            let mut filler = Filler::new(&trie, true, 60, false);
+            let filled = filler.fill(&fixed).unwrap();
+
+            assert!(!filled.contents.contains(&' '));
+            for (given, c) in fixed.contents.iter().zip(filled.contents.iter()) {
+                if *given != ' ' {
+                    assert_eq!(given, c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn block_chars() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX
+X:XX
+XXXX
+XXXX
 ",
         ))
         .unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
 
-        let now = Instant::now();
-        let trie = Trie::load("ro_dex_080").expect("Failed to load trie");
-        let mut filler = Filler::new(&trie, true, 60, true);
-        let filled_puz = filler.fill(&grid).unwrap();
-        println!("Filled in {} seconds.", now.elapsed().as_secs());
-        println!("{}", filled_puz);
+        let mut filler = Filler::new(&trie, false, 60, false);
+        let filled = filler.fill(&grid).unwrap();
+        assert_eq!(':', filled.contents[5]);
+        assert!(!filled.contents.contains(&' '));
+
+        let mut filler = Filler::new(&trie, false, 60, false);
+        filler.with_block_chars(BlockChars::new(&['.']));
+        let filled = filler.fill(&grid).unwrap();
+        assert!(filled.contents[5].is_alphabetic());
+        assert!(!filled.contents.contains(&' '));
+    }
+
+    #[test]
+    fn with_seed() {
+        let grid = Crossword::parse(String::from(
+            "
+XXXX.
+XXXXX
+XXXXX
+.XXXX
+",
+        ))
+        .unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let fill = |seed: u64| {
+            let mut filler = Filler::new(&trie, true, 60, false);
+            filler.with_seed(seed);
+            filler.fill(&grid).unwrap()
+        };
+
+        let first = fill(7);
+        assert_eq!(first, fill(7));
+        assert!((0..5).any(|seed| fill(seed) != first));
+    }
+
+    #[test]
+    fn slot_constraints() {
+        let grid = Crossword::parse(String::from("ABX\nX.X\nXXQ")).unwrap();
+        let trie = Trie::build(vec![
+            String::from("ABC"),
+            String::from("ABD"),
+            String::from("ADE"),
+            String::from("AXE"),
+            String::from("CAT"),
+        ]);
+        let mut filler = Filler::new(&trie, false, 60, false);
+
+        assert_eq!(
+            vec![
+                (WordBoundary::new(0, 0, 3, Direction::Across), 2),
+                (WordBoundary::new(2, 0, 3, Direction::Across), 0),
+                (WordBoundary::new(0, 0, 3, Direction::Down), 4),
+                (WordBoundary::new(0, 2, 3, Direction::Down), 0),
+            ],
+            filler.slot_constraints(&grid)
+        );
+
+        let grid = Crossword::parse(String::from("ABC\nX.X\nXXX")).unwrap();
+        filler.with_blocklist(["ADE"].iter().map(|word| word.to_string()).collect());
+        let constraints = filler.slot_constraints(&grid);
+        assert_eq!(3, constraints.len());
+        assert!(constraints.contains(&(WordBoundary::new(0, 0, 3, Direction::Down), 3)));
+        assert!(constraints.contains(&(WordBoundary::new(0, 2, 3, Direction::Down), 1)));
+    }
+
+    #[test]
+    fn on_progress() {
+        let grid = Crossword::parse(String::from("XXX\nX.X\nXXX")).unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
+        // the callback needn't be Send or Sync
+        let reports = Rc::new(RefCell::new(vec![]));
+
+        let mut filler = Filler::new(&trie, false, 60, false);
+        let sink = Rc::clone(&reports);
+        filler.on_progress(Box::new(move |progress| sink.borrow_mut().push(progress)));
+        filler.fill(&grid).unwrap();
+
+        let reports: &Vec<Progress> = &reports.borrow();
+        assert!(!reports.is_empty());
+        assert_eq!(1, reports[0].candidates);
+        assert_eq!(grid, reports[0].current);
+    }
+
+    #[test]
+    fn fill_best_effort() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let grid = Crossword::parse(String::from(
+            "
+XXXXXXXX.
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+.XXXXXXXX
+",
+        ))
+        .unwrap();
+
+        let mut filler = Filler::new(&trie, false, 0, false);
+        let (partial, complete) = filler.fill_best_effort(&grid);
+        assert!(!complete);
+        assert!(partial.contents.contains(&' '));
+        assert!(partial.contents.iter().any(|c| c.is_alphabetic()));
+
+        let grid = Crossword::parse(String::from("XXX\nXXX\nXXX")).unwrap();
+        let mut filler = Filler::new(&trie, false, 60, false);
+        let (filled, complete) = filler.fill_best_effort(&grid);
+        assert!(complete);
+        assert!(!filled.contents.contains(&' '));
+
+        let empty = Trie::build(vec![]);
+        let mut filler = Filler::new(&empty, false, 60, false);
+        assert_eq!((grid.clone(), false), filler.fill_best_effort(&grid));
+    }
+
+    #[test]
+    fn timeout_returns_best_partial() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let grids = [
+            "
+XXXXXXXX.
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+XXXXXXXXX
+.XXXXXXXX
+",
+            "
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+XXXXXXXXXXX
+",
+        ];
+
+        for grid in grids.iter() {
+            let grid = Crossword::parse(String::from(*grid)).unwrap();
+            let mut filler = Filler::new(&trie, false, 0, false);
+
+            match filler.fill(&grid) {
+                Err(FillError::Timeout {
+                    best_partial,
+                    filled_fraction,
+                }) => {
+                    assert!(filled_fraction > 0.0 && filled_fraction < 1.0);
+                    assert_eq!(grid.width, best_partial.width);
+                    assert_eq!(grid.height, best_partial.height);
+                    for (given, c) in grid.contents.iter().zip(best_partial.contents.iter()) {
+                        if *given != ' ' {
+                            assert_eq!(given, c);
+                        }
+                    }
+                }
+                result => panic!("Expected a timeout, got {:?}", result),
+            }
+        }
     }
 
     #[test]
@@ -221,4 +3015,179 @@ XXXXX
         println!("Filled in {} seconds.", now.elapsed().as_secs());
         println!("{}", filled_puz);
     }
+
+    #[test]
+    fn fill_all() {
+        let grid = Crossword::parse_from_file("grids/waffle.txt").unwrap();
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, false);
+
+        let solutions = filler.fill_all(&grid, 3);
+
+        assert_eq!(3, solutions.len());
+        let distinct: HashSet<&Crossword> = solutions.iter().collect();
+        assert_eq!(3, distinct.len());
+        for solution in solutions.iter() {
+            assert!(!solution.contents.contains(&' '), "{}", solution);
+        }
+        assert!(filler.fill_all(&grid, 0).is_empty());
+
+        let trie = Trie::build(vec![
+            String::from("AB"),
+            String::from("CD"),
+            String::from("AC"),
+            String::from("BD"),
+        ]);
+        let mut filler = Filler::new(&trie, false, 60, false);
+        let open = Crossword::parse(String::from("XX\nXX")).unwrap();
+        assert_eq!(2, filler.fill_all(&open, 10).len());
+    }
+
+    /// Fills `grid` deterministically and fails if the search explored more candidates
+    /// than `max_candidates`, turning heuristic regressions into test failures.
+    fn assert_candidate_count_below(grid: &str, trie: &Trie, max_candidates: usize) {
+        let grid = Crossword::parse(String::from(grid)).unwrap();
+        let mut filler = Filler::new(trie, false, 60, false);
+        filler.fill(&grid).unwrap();
+
+        let stats = filler.stats();
+        assert!(stats.candidate_count > 0);
+        assert!(
+            stats.candidate_count <= max_candidates,
+            "Explored {} candidates, more than the {} allowed:\n{}",
+            stats.candidate_count,
+            max_candidates,
+            grid
+        );
+    }
+
+    #[test]
+    fn candidate_count_medium_grid() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        // 19 candidates when recorded
+        assert_candidate_count_below(
+            "
+XXXX...
+XXXX...
+XXXX...
+XXXXXXX
+...XXXX
+...XXXX
+...XXXX
+",
+            &trie,
+            200,
+        );
+    }
+
+    #[test]
+    fn candidate_count_waffle_grid() {
+        let waffle = "
+XXXXX
+X.X.X
+XXXXX
+X.X.X
+XXXXX
+";
+        // 7 candidates with either word list when recorded
+        let trie = Trie::load_default().expect("Failed to load trie");
+        assert_candidate_count_below(waffle, &trie, 100);
+        let trie = Trie::load("ro_dex_000").expect("Failed to load trie");
+        assert_candidate_count_below(waffle, &trie, 100);
+    }
+
+    #[test]
+    fn fill_stats_reset_between_fills() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let mut filler = Filler::new(&trie, false, 60, false);
+        assert_eq!(FillStats::default(), filler.stats());
+
+        let grid = Crossword::parse(String::from("XXXX\nXXXX\nXXXX\nXXXX")).unwrap();
+        filler.fill(&grid).unwrap();
+        let first = filler.stats();
+        assert!(first.candidate_count > 0);
+
+        filler.fill(&grid).unwrap();
+        assert_eq!(first.candidate_count, filler.stats().candidate_count);
+    }
+
+    #[test]
+    fn step_is_reproducible() {
+        let trie = Trie::load_default().expect("Failed to load trie");
+        let grid = Crossword::parse(String::from("XXXX\nXXXX\nXXXX\nXXXX")).unwrap();
+        let steps = |seed: u64| {
+            let mut filler = Filler::new(&trie, true, 10, false);
+            filler.with_seed(seed);
+            let mut crossword = grid.clone();
+            let mut steps = vec![];
+            for _ in 0..3 {
+                let (slot, next) = filler.step(&crossword).unwrap();
+                steps.push((slot, next.to_string()));
+                crossword = next;
+            }
+            steps
+        };
+
+        let first = steps(7);
+
+        assert_eq!(first, steps(7));
+        assert_ne!(first[0].1, first[1].1);
+        let filled = Crossword::parse(String::from("AB\nCD")).unwrap();
+        let mut filler = Filler::new(&trie, true, 10, false);
+        assert_eq!(
+            Err(FillError::InvalidGrid(String::from(
+                "No fillable words found"
+            ))),
+            filler.step(&filled)
+        );
+    }
+
+    #[test]
+    fn explain_rejection() {
+        let trie = Trie::build(vec![
+            String::from("AB"),
+            String::from("AC"),
+            String::from("BD"),
+            String::from("CD"),
+        ]);
+        let grid = Crossword::parse(String::from(
+            "XX
+XD",
+        ))
+        .unwrap();
+        let top = WordBoundary::new(0, 0, 2, Direction::Across);
+        let bottom = WordBoundary::new(1, 0, 2, Direction::Across);
+        let mut filler = Filler::new(&trie, false, 10, false);
+
+        assert_eq!(
+            Some(RejectionReason::WrongLength {
+                expected: 2,
+                length: 3
+            }),
+            filler.explain_rejection(&grid, &top, "ABC")
+        );
+        assert_eq!(
+            Some(RejectionReason::ConflictsWithGiven {
+                index: 1,
+                given: 'D'
+            }),
+            filler.explain_rejection(&grid, &bottom, "AC")
+        );
+        assert_eq!(
+            Some(RejectionReason::KillsCrossing {
+                index: 0,
+                crossing: WordBoundary::new(0, 0, 2, Direction::Down)
+            }),
+            filler.explain_rejection(&grid, &top, "DB")
+        );
+        assert_eq!(
+            Some(RejectionReason::KillsCrossing {
+                index: 1,
+                crossing: WordBoundary::new(0, 1, 2, Direction::Down)
+            }),
+            filler.explain_rejection(&grid, &top, "AD")
+        );
+        assert_eq!(None, filler.explain_rejection(&grid, &top, "ab"));
+        assert_eq!(None, filler.explain_rejection(&grid, &bottom, "CD"));
+    }
 }
@@ -5,62 +5,333 @@ Utility methods consumed in filling a crossword puzzle.
 use crate::{
     crossword::{Direction, WordIterator},
     fill::cache::CachedIsViable,
-    parse::WordBoundary,
+    parse::{BlockChars, WordBoundary},
     trie::Trie,
     Crossword, FxHashMap,
 };
 
 use core::hash::{BuildHasherDefault, Hash};
-use rustc_hash::{FxHashSet, FxHasher};
-use std::{collections, hash::Hasher};
+use rustc_hash::FxHasher;
+use serde::{Deserialize, Serialize};
+use std::{collections, fmt, hash::Hasher, rc::Rc, time::Duration};
 
 pub mod cache;
 pub mod filler;
 
+/// Why filling a crossword failed.
+#[derive(Debug, PartialEq, Clone)]
+pub enum FillError {
+    /// The time limit was reached. `best_partial` is the most filled grid the search
+    /// reached, and `filled_fraction` the fraction of its letter squares that are filled.
+    Timeout {
+        best_partial: Crossword,
+        filled_fraction: f64,
+    },
+    /// Every candidate was explored without finding a solution.
+    NoSolution,
+    /// The grid cannot be filled as given, e.g. some given letters match no word.
+    InvalidGrid(String),
+    /// The word list holds no words, e.g. it was built from an empty file.
+    EmptyDictionary,
+}
+
+impl fmt::Display for FillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FillError::Timeout {
+                filled_fraction, ..
+            } => write!(
+                f,
+                "Time limit reached with {:.0}% of the grid filled",
+                filled_fraction * 100.0
+            ),
+            FillError::NoSolution => write!(f, "No valid solution found"),
+            FillError::InvalidGrid(message) => write!(f, "{}", message),
+            FillError::EmptyDictionary => write!(f, "The word list is empty"),
+        }
+    }
+}
+
+impl std::error::Error for FillError {}
+
+impl From<FillError> for String {
+    fn from(error: FillError) -> String {
+        error.to_string()
+    }
+}
+
+/// Why a word can't be placed in a slot, see `Filler::explain_rejection`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum RejectionReason {
+    /// The word has `length` letters and the slot `expected` squares.
+    WrongLength { expected: usize, length: usize },
+    /// The letter at `index` of the word differs from the letter `given` in the grid.
+    ConflictsWithGiven { index: usize, given: char },
+    /// No word fits the slot `crossing`, which crosses the word at `index`, once the word
+    /// is placed.
+    KillsCrossing {
+        index: usize,
+        crossing: WordBoundary,
+    },
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectionReason::WrongLength { expected, length } => {
+                write!(f, "The word has {} letters instead of {}", length, expected)
+            }
+            RejectionReason::ConflictsWithGiven { index, given } => write!(
+                f,
+                "Letter {} of the word conflicts with the given {}",
+                index + 1,
+                given
+            ),
+            RejectionReason::KillsCrossing { index, .. } => write!(
+                f,
+                "No word fits the slot crossing letter {} of the word",
+                index + 1
+            ),
+        }
+    }
+}
+
+/// A slot completed while filling, see `Filler::with_trace`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TraceStep {
+    pub number: usize,
+    pub direction: Direction,
+    pub word: String,
+}
+
+/// Statistics of a fill, see `Filler::stats`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct FillStats {
+    /// The number of partial grids explored.
+    pub candidate_count: usize,
+    pub elapsed: Duration,
+}
+
+/// The progress of a fill, reported periodically to `Filler::on_progress`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Progress {
+    /// The number of partial grids explored so far.
+    pub candidates: usize,
+    pub elapsed_ms: u64,
+    /// The partial grid being explored.
+    pub current: Crossword,
+}
+
+/// A grid being filled with the configuration of its `Filler`, to save a construction
+/// session and resume it later, see `Filler::state` and `Filler::from_state`. The scorer
+/// and the cost function can't be saved and have to be set again.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FillerState {
+    /// The grid as written by `Crossword`'s `Display`, with `X` for open squares.
+    pub grid: String,
+    pub random: bool,
+    pub max_time_seconds: u64,
+    pub heuristic: SlotHeuristic,
+    pub start_slot_offset: usize,
+    pub word_scores: bool,
+    pub letter_diversity: bool,
+    pub strict: bool,
+    pub forbid_reversed: bool,
+    pub max_candidates_per_slot: Option<usize>,
+    pub prune_below_candidates: Option<usize>,
+    pub max_duplicate: usize,
+    /// The blocklisted words, uppercase and sorted.
+    pub blocklist: Vec<String>,
+    pub required_words: Vec<String>,
+    pub block_chars: BlockChars,
+    pub themers: Vec<WordBoundary>,
+    pub answers: Vec<(usize, Direction, String)>,
+}
+
+impl FillerState {
+    /// Parses the saved grid.
+    pub fn crossword(&self) -> Result<Crossword, String> {
+        Crossword::parse(self.grid.clone())
+    }
+}
+
 /// The sole trait involved in filling crossword puzzles. Algorithms that
 /// conform to this interface will be easy to compare against the existing
 /// algorithm.
 pub trait Fill {
-    fn fill(&mut self, crossword: &Crossword) -> Result<Crossword, String>;
+    /// Fills every open square of `crossword`. Letters already in the grid, whether
+    /// whole words or scattered squares, are hard constraints: the solution keeps them
+    /// as given. Since `Crossword::parse` reads `X` as an open square, a fixed `X`
+    /// cannot be given in the input.
+    fn fill(&mut self, crossword: &Crossword) -> Result<Crossword, FillError>;
+}
+
+/// Decides which unfilled slot the filler tries to fill next.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default, Serialize, Deserialize)]
+pub enum SlotHeuristic {
+    /// Pick the slot with the fewest candidate words.
+    #[default]
+    FewestCandidates,
+    /// Pick the slot whose most constrained open square has the lowest entropy of
+    /// possible letters, see `min_letter_entropy`.
+    MinimumEntropy,
+    /// Pick the longest slot, the one with the fewest candidate words among slots of the
+    /// same length. Long slots are the hardest to fill late, but this is far slower than
+    /// the other heuristics on full-size grids.
+    LongestFirst,
+    /// Pick the across slots first, top to bottom, then the down slots. A word is only
+    /// placed if every crossing slot can still be completed, so a grid without blocks is
+    /// filled row by row as a constraint system on its columns, exploring far fewer
+    /// candidates than the other heuristics.
+    AcrossFirst,
+}
+
+impl SlotHeuristic {
+    /// Every heuristic, e.g. to compare them.
+    pub const ALL: [SlotHeuristic; 4] = [
+        SlotHeuristic::FewestCandidates,
+        SlotHeuristic::MinimumEntropy,
+        SlotHeuristic::LongestFirst,
+        SlotHeuristic::AcrossFirst,
+    ];
+}
+
+/// Computes, for every open square of a slot, the Shannon entropy (in bits) of the letters
+/// the `words` that fit the slot put in that square, and returns the lowest one.
+///
+/// A low entropy means the square is nearly decided, so filling the slot is unlikely to
+/// branch much. A slot without candidate words has an entropy of zero, so dead ends are
+/// picked (and discarded) right away.
+pub fn min_letter_entropy<T: Iterator<Item = char>>(pattern: T, words: &[String]) -> f64 {
+    if words.is_empty() {
+        return 0.0;
+    }
+    let open_squares: Vec<usize> = pattern
+        .enumerate()
+        .filter(|(_, c)| *c == ' ')
+        .map(|(index, _)| index)
+        .collect();
+    let mut counts: Vec<FxHashMap<char, usize>> = vec![FxHashMap::default(); open_squares.len()];
+    for word in words {
+        let chars: Vec<char> = word.chars().collect();
+        for (square, index) in open_squares.iter().enumerate() {
+            *counts[square].entry(chars[*index]).or_insert(0) += 1;
+        }
+    }
+
+    let total = words.len() as f64;
+    counts
+        .iter()
+        .map(|letter_counts| {
+            letter_counts
+                .values()
+                .map(|count| {
+                    let p = *count as f64 / total;
+                    -p * p.log2()
+                })
+                .sum::<f64>()
+        })
+        .fold(f64::INFINITY, f64::min)
 }
 
 /// Determines whether a given crossword puzzle is viable. This performs several
 /// checks to decide whether a partially complete crossword should be considered
 /// for further filling, or should be discarded.
 ///
-/// `already_used` is reused across runs to avoid allocating and should be `clear`ed
-/// between calls. Currently this method is fairly hot.
+/// `committed` counts the complete words of the grid outside `word_boundaries`, by
+/// `word_key`. Currently this method is fairly hot.
 ///
 /// Viability checks include: (1) is there at least one valid word that matches this partial
-/// fill; (2) does this crossword include any repeated complete words.
+/// fill; (2) does this crossword include any complete word more than `max_duplicate` times,
+/// unless `max_duplicate` is 0.
 pub fn is_viable_reuse(
     candidate: &Crossword,
     word_boundaries: &[&WordBoundary],
     trie: &Trie,
-    mut already_used: FxHashSet<u64>,
+    committed: &FxHashMap<u64, usize>,
+    max_duplicate: usize,
     is_viable_cache: &mut CachedIsViable,
-) -> (bool, FxHashSet<u64>) {
+) -> bool {
+    // the complete words of `word_boundaries` so far, few enough to scan
+    let mut complete = vec![];
     for word_boundary in word_boundaries {
         let iter = WordIterator::new(candidate, word_boundary);
 
-        let mut hasher = FxHasher::default();
-        let mut full = true;
-        for c in iter.clone() {
-            c.hash(&mut hasher);
-            full = full && c != ' ';
+        let (key, full) = word_key(iter.clone());
+        if full && max_duplicate > 0 {
+            complete.push(key);
+            let count = complete.iter().filter(|other| **other == key).count();
+            if committed.get(&key).unwrap_or(&0) + count > max_duplicate {
+                return false;
+            }
         }
-        let key = hasher.finish();
 
-        if full && already_used.contains(&key) {
-            return (false, already_used);
+        if !is_viable_cache.is_viable(iter, trie) {
+            return false;
         }
-        already_used.insert(key);
+    }
+    true
+}
 
-        if !is_viable_cache.is_viable(iter, trie) {
-            return (false, already_used);
+/// The complete words of a candidate grid, as their slot, an index in the word boundaries
+/// of the grid, and their `word_key`. The candidates of a search branch share the words
+/// of their parent and add the ones they complete, so the words committed along the
+/// branch are known without scanning the grid.
+#[derive(Clone, Default)]
+pub(crate) struct CommittedWords {
+    last: Option<Rc<CommittedWord>>,
+}
+
+struct CommittedWord {
+    slot: usize,
+    key: u64,
+    previous: Option<Rc<CommittedWord>>,
+}
+
+impl CommittedWords {
+    /// Collects the complete words of `crossword`.
+    pub(crate) fn new(crossword: &Crossword, word_boundaries: &[WordBoundary]) -> CommittedWords {
+        let mut committed = CommittedWords::default();
+        for (slot, word_boundary) in word_boundaries.iter().enumerate() {
+            let (key, full) = word_key(WordIterator::new(crossword, word_boundary));
+            if full {
+                committed = committed.with(slot, key);
+            }
         }
+        committed
+    }
+
+    /// Returns these words and the word `key` completed in `slot`.
+    pub(crate) fn with(&self, slot: usize, key: u64) -> CommittedWords {
+        CommittedWords {
+            last: Some(Rc::new(CommittedWord {
+                slot,
+                key,
+                previous: self.last.clone(),
+            })),
+        }
+    }
+
+    /// Iterates the words as `(slot, key)`, the last completed first.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let mut next = self.last.as_deref();
+        std::iter::from_fn(move || {
+            let word = next?;
+            next = word.previous.as_deref();
+            Some((word.slot, word.key))
+        })
     }
-    (true, already_used)
+}
+
+/// Hashes the letters of a word, returning the hash and whether the word is complete.
+pub fn word_key<T: Iterator<Item = char>>(iter: T) -> (u64, bool) {
+    let mut hasher = FxHasher::default();
+    let mut full = true;
+    for c in iter {
+        c.hash(&mut hasher);
+        full = full && c != ' ';
+    }
+    (hasher.finish(), full)
 }
 
 pub fn fill_one_word(candidate: &Crossword, iter: &WordIterator, word: &str) -> Crossword {
@@ -105,7 +376,9 @@ pub fn fill_one_word(candidate: &Crossword, iter: &WordIterator, word: &str) ->
 
     Crossword {
         contents: result_contents,
-        ..*candidate
+        width: candidate.width,
+        height: candidate.height,
+        rebus: candidate.rebus.clone(),
     }
 }
 
@@ -188,7 +461,41 @@ pub fn words_orthogonal_to_word<'s>(
 mod tests {
     use crate::{crossword::Direction, fill::WordIterator, parse::WordBoundary, Crossword};
 
-    use super::fill_one_word;
+    use super::{fill_one_word, min_letter_entropy, word_key, CommittedWords};
+
+    #[test]
+    fn committed_words_works() {
+        let c = Crossword::parse(String::from("ABX\nC.X")).unwrap();
+        let word_boundaries = crate::parse::parse_word_boundaries(&c);
+
+        let committed = CommittedWords::new(&c, &word_boundaries);
+        let slots: Vec<usize> = committed.iter().map(|(slot, _)| slot).collect();
+        let complete: Vec<usize> = (0..word_boundaries.len())
+            .filter(|slot| word_key(WordIterator::new(&c, &word_boundaries[*slot])).1)
+            .rev()
+            .collect();
+        assert_eq!(1, slots.len());
+        assert_eq!(complete, slots);
+
+        let extended = committed.with(7, 42);
+        assert_eq!(Some((7, 42)), extended.iter().next());
+        assert_eq!(slots.len() + 1, extended.iter().count());
+        assert_eq!(slots.len(), committed.iter().count());
+    }
+
+    #[test]
+    fn min_letter_entropy_works() {
+        let words = vec![
+            String::from("BASS"),
+            String::from("BESS"),
+            String::from("BOSS"),
+            String::from("BUSS"),
+        ];
+
+        assert_eq!(2.0, min_letter_entropy("B SS".chars(), &words));
+        assert_eq!(0.0, min_letter_entropy("  SS".chars(), &words));
+        assert_eq!(0.0, min_letter_entropy("B SS".chars(), &[]));
+    }
 
     #[test]
 
@@ -214,7 +521,7 @@ ghi
                         direction: Direction::Across,
                     },
                 ),
-                &String::from("cat")
+                &String::from("CAT")
             ),
             Crossword::parse(String::from(
                 "
@@ -238,7 +545,7 @@ ghi
                         direction: Direction::Down,
                     }
                 ),
-                &String::from("cat"),
+                &String::from("CAT"),
             ),
             Crossword::parse(String::from(
                 "
@@ -0,0 +1,116 @@
+/*!
+Renders a crossword as a minimal HTML table.
+*/
+
+use crate::crossword::Crossword;
+use crate::parse::{BLACK_SQUARE, VOID_SQUARE};
+use std::fmt::Write;
+
+impl Crossword {
+    /// Renders the crossword as an HTML `<table>` with one `<td>` per square.
+    ///
+    /// No styling is included so callers can supply their own CSS: the table has the
+    /// `crossword` class, black squares have the `block` class, void squares the `void`
+    /// class and clue numbers are wrapped in a `<span class="number">`. Rebus squares hold
+    /// all their letters.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<table class=\"crossword\">\n");
+        let mut current_row = None;
+        for (row, col, number, c) in self.cells_with_numbers() {
+            if current_row != Some(row) {
+                if current_row.is_some() {
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("<tr>");
+                current_row = Some(row);
+            }
+            if BLACK_SQUARE.contains(&c) {
+                html.push_str("<td class=\"block\"></td>");
+                continue;
+            }
+            if c == VOID_SQUARE {
+                html.push_str("<td class=\"void\"></td>");
+                continue;
+            }
+            html.push_str("<td>");
+            if let Some(number) = number {
+                write!(html, "<span class=\"number\">{}</span>", number).unwrap();
+            }
+            if c != ' ' {
+                let letters = self.rebus(row, col).map_or(c.to_string(), String::from);
+                html.push_str(&escape(&letters));
+            }
+            html.push_str("</td>");
+        }
+        html.push_str("</tr>\n</table>\n");
+        html
+    }
+}
+
+/// Escapes the characters with a special meaning in HTML and XML text and attribute values,
+/// for the exporters writing markup.
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crossword::Crossword;
+
+    #[test]
+    fn to_html_works() {
+        let c = Crossword::parse(String::from(
+            "
+SIAM
+N.EM
+RYAX
+",
+        ))
+        .unwrap();
+
+        let html = c.to_html();
+
+        assert_eq!(12, html.matches("<td").count());
+        assert_eq!(3, html.matches("<tr>").count());
+        assert_eq!(1, html.matches("<td class=\"block\">").count());
+        assert!(html.starts_with(
+            "<table class=\"crossword\">\n<tr><td><span class=\"number\">1</span>S</td><td>I</td>"
+        ));
+        assert!(html.ends_with("<td>A</td><td></td></tr>\n</table>\n"));
+    }
+
+    #[test]
+    fn rebus_squares_keep_their_letters() {
+        let c = Crossword::parse(String::from("[HEART]S\nAT")).unwrap();
+
+        let html = c.to_html();
+
+        assert!(html.contains("<td><span class=\"number\">1</span>HEART</td><td>"));
+        assert!(html.contains("<td><span class=\"number\">3</span>A</td><td>T</td>"));
+    }
+
+    #[test]
+    fn escape_works() {
+        assert_eq!(
+            "A&amp;B &lt;i&gt; &quot;x&quot;",
+            super::escape("A&B <i> \"x\"")
+        );
+    }
+
+    #[test]
+    fn void_squares_are_empty() {
+        let c = Crossword::parse(String::from(" A \nBCD\n E ")).unwrap();
+
+        let html = c.to_html();
+
+        assert_eq!(9, html.matches("<td").count());
+        assert_eq!(4, html.matches("<td class=\"void\"></td>").count());
+        assert!(!html.contains('~'));
+        assert!(html.starts_with(
+            "<table class=\"crossword\">\n<tr><td class=\"void\"></td><td><span class=\"number\">1</span>A</td>"
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+/*!
+Renders the pattern of black squares of a crossword as a PNG image, and reads it
+back from such an image. Available with the `png` feature.
+
+Letters are neither drawn nor recognized: the images are meant to share templates.
+*/
+
+use crate::crossword::Crossword;
+use crate::parse::VOID_SQUARE;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// The shade of the lines between squares.
+const BORDER_SHADE: u8 = 160;
+
+/// The shade of void squares, lighter than the lines between squares.
+const VOID_SHADE: u8 = 208;
+
+/// Pixels darker than this are read as part of a black square.
+const BLACK_THRESHOLD: u32 = 128;
+
+/// Pixels of these shades are read as part of a void square.
+const VOID_RANGE: Range<u32> = 192..224;
+
+impl Crossword {
+    /// Renders the grid as a grayscale PNG image with squares of `cell_size` pixels.
+    /// Black squares are black, void squares light gray and other squares white, with gray
+    /// lines between squares.
+    /// Err is returned if `cell_size` is 0 or the image cannot be encoded.
+    pub fn to_png(&self, cell_size: u32) -> Result<Vec<u8>, String> {
+        if cell_size == 0 {
+            return Err("Cell size must be positive".to_string());
+        }
+        let image_width = self.width as u32 * cell_size;
+        let image_height = self.height as u32 * cell_size;
+        let mut pixels = Vec::with_capacity((image_width * image_height) as usize);
+        for y in 0..image_height {
+            for x in 0..image_width {
+                let (row, col) = ((y / cell_size) as usize, (x / cell_size) as usize);
+                let shade = if self.is_black(row, col) {
+                    0
+                } else if self.contents[row * self.width + col] == VOID_SQUARE {
+                    VOID_SHADE
+                } else if x % cell_size == 0 || y % cell_size == 0 {
+                    BORDER_SHADE
+                } else {
+                    255
+                };
+                pixels.push(shade);
+            }
+        }
+
+        let mut bytes = vec![];
+        let mut encoder = png::Encoder::new(&mut bytes, image_width, image_height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer
+            .write_image_data(&pixels)
+            .map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
+
+    /// Reads the black squares of a `width` x `height` grid from a PNG image, such as one
+    /// rendered by `Crossword::to_png`, by sampling the center of every square. Dark
+    /// squares become `.` black squares, light gray squares void squares and all other
+    /// squares are open.
+    /// Err is returned if the bytes are not a PNG image or the image is smaller than the grid.
+    pub fn from_image(bytes: &[u8], width: usize, height: usize) -> Result<Crossword, String> {
+        if width == 0 || height == 0 {
+            return Err("Empty grid".to_string());
+        }
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut pixels).map_err(|e| e.to_string())?;
+
+        let image_width = frame.width as usize;
+        let image_height = frame.height as usize;
+        if image_width < width || image_height < height {
+            return Err(format!(
+                "Image of {}x{} pixels is smaller than the {}x{} grid",
+                image_width, image_height, width, height
+            ));
+        }
+        let samples = frame.color_type.samples();
+        // alpha is ignored, only the gray or color samples count
+        let color_samples = if samples < 3 { 1 } else { 3 };
+
+        let contents = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .map(|(row, col)| {
+                let x = (2 * col + 1) * image_width / (2 * width);
+                let y = (2 * row + 1) * image_height / (2 * height);
+                let start = y * frame.line_size + x * samples;
+                let luminance = pixels[start..start + color_samples]
+                    .iter()
+                    .map(|sample| *sample as u32)
+                    .sum::<u32>()
+                    / color_samples as u32;
+                if luminance < BLACK_THRESHOLD {
+                    '.'
+                } else if VOID_RANGE.contains(&luminance) {
+                    VOID_SQUARE
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        Ok(Crossword {
+            contents,
+            width,
+            height,
+            rebus: BTreeMap::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crossword::Crossword;
+
+    #[test]
+    fn png_round_trip_works() {
+        let template = Crossword::generate_template(9, 7, 0.2, 3).unwrap();
+
+        for cell_size in [1, 7, 20].iter() {
+            let bytes = template.to_png(*cell_size).unwrap();
+            let read = Crossword::from_image(&bytes, 9, 7).unwrap();
+            assert_eq!(template, read);
+        }
+        assert!(template.to_png(0).is_err());
+    }
+
+    #[test]
+    fn void_squares_round_trip() {
+        let plus = Crossword::parse(String::from(" A \nBCD\n E ")).unwrap();
+
+        for cell_size in [1, 10].iter() {
+            let bytes = plus.to_png(*cell_size).unwrap();
+            let read = Crossword::from_image(&bytes, 3, 3).unwrap();
+            assert_eq!(plus.clear_letters(), read);
+        }
+    }
+
+    #[test]
+    fn from_image_ignores_letters() {
+        let filled = Crossword::parse(String::from(
+            "
+SIAM
+N.EM
+RYAL
+",
+        ))
+        .unwrap();
+
+        let bytes = filled.to_png(10).unwrap();
+        let read = Crossword::from_image(&bytes, 4, 3).unwrap();
+        assert_eq!("XXXX\nX.XX\nXXXX", read.to_string());
+    }
+
+    #[test]
+    fn from_image_rejects_invalid_input() {
+        assert!(Crossword::from_image(b"not a png", 3, 3).is_err());
+        let bytes = Crossword::parse(String::from("X.X"))
+            .unwrap()
+            .to_png(1)
+            .unwrap();
+        assert!(Crossword::from_image(&bytes, 4, 1).is_err());
+    }
+}
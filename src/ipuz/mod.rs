@@ -0,0 +1,144 @@
+/*!
+Exports a crossword to the open [ipuz](http://ipuz.org) JSON format, read by many
+web-based solvers.
+*/
+
+use crate::crossword::{Crossword, Direction};
+use crate::parse::{BLACK_SQUARE, VOID_SQUARE};
+use serde_json::{json, Value};
+
+/// The texts of an exported puzzle.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default)]
+pub struct Metadata {
+    pub title: String,
+    pub author: String,
+    pub copyright: String,
+}
+
+/// Formats the crossword as an ipuz crossword document.
+///
+/// The `puzzle` grid has `#` for black squares, the clue number of squares that start a
+/// word and 0 for the other squares. The `solution` grid has the letters of the crossword,
+/// all the letters of rebus squares, `#` for black squares and null for open squares. Void squares are null in both grids,
+/// as the format omits them. Clues are left empty.
+pub fn to_ipuz(crossword: &Crossword, metadata: &Metadata) -> Value {
+    let mut puzzle = vec![vec![]; crossword.height];
+    let mut solution = vec![vec![]; crossword.height];
+    for (row, col, number, c) in crossword.cells_with_numbers() {
+        let (puzzle_cell, solution_cell) = if c == VOID_SQUARE {
+            (Value::Null, Value::Null)
+        } else if BLACK_SQUARE.contains(&c) {
+            (json!("#"), json!("#"))
+        } else if c == ' ' {
+            (json!(number.unwrap_or(0)), Value::Null)
+        } else {
+            let letters = crossword
+                .rebus(row, col)
+                .map_or(c.to_string(), String::from);
+            (json!(number.unwrap_or(0)), json!(letters))
+        };
+        puzzle[row].push(puzzle_cell);
+        solution[row].push(solution_cell);
+    }
+
+    let clues = |direction: Direction| -> Vec<Value> {
+        crossword
+            .numbered_entries()
+            .iter()
+            .filter(|entry| entry.word_boundary.direction == direction)
+            .map(|entry| json!([entry.number, ""]))
+            .collect()
+    };
+
+    json!({
+        "version": "http://ipuz.org/v2",
+        "kind": ["http://ipuz.org/crossword#1"],
+        "title": metadata.title,
+        "author": metadata.author,
+        "copyright": metadata.copyright,
+        "dimensions": {"width": crossword.width, "height": crossword.height},
+        "block": "#",
+        "empty": 0,
+        "puzzle": puzzle,
+        "solution": solution,
+        "clues": {"Across": clues(Direction::Across), "Down": clues(Direction::Down)},
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crossword::Crossword;
+
+    use super::{to_ipuz, Metadata};
+
+    #[test]
+    fn to_ipuz_works() {
+        let c = Crossword::parse(String::from(
+            "
+SIAM
+N:EM
+RYAX
+",
+        ))
+        .unwrap();
+        let metadata = Metadata {
+            title: String::from("title"),
+            author: String::from("author"),
+            copyright: String::from("copyright"),
+        };
+
+        let expected: serde_json::Value = serde_json::from_str(
+            r##"{
+  "version": "http://ipuz.org/v2",
+  "kind": ["http://ipuz.org/crossword#1"],
+  "title": "title",
+  "author": "author",
+  "copyright": "copyright",
+  "dimensions": {"width": 4, "height": 3},
+  "block": "#",
+  "empty": 0,
+  "puzzle": [
+    [1, 0, 2, 3],
+    [0, "#", 4, 0],
+    [5, 0, 0, 0]
+  ],
+  "solution": [
+    ["S", "I", "A", "M"],
+    ["N", "#", "E", "M"],
+    ["R", "Y", "A", null]
+  ],
+  "clues": {
+    "Across": [[1, ""], [4, ""], [5, ""]],
+    "Down": [[1, ""], [2, ""], [3, ""]]
+  }
+}"##,
+        )
+        .unwrap();
+        assert_eq!(expected, to_ipuz(&c, &metadata));
+    }
+
+    #[test]
+    fn void_squares_are_omitted() {
+        let c = Crossword::parse(String::from(" A \nBCD\n E ")).unwrap();
+
+        let ipuz = to_ipuz(&c, &Metadata::default());
+
+        assert_eq!(serde_json::json!([null, 1, null]), ipuz["puzzle"][0]);
+        assert_eq!(serde_json::json!([2, 0, 0]), ipuz["puzzle"][1]);
+        assert_eq!(serde_json::json!([null, "E", null]), ipuz["solution"][2]);
+    }
+
+    #[test]
+    fn rebus_squares_keep_their_letters() {
+        let c = Crossword::parse(String::from(
+            "[HEART]S
+AT",
+        ))
+        .unwrap();
+
+        let ipuz = to_ipuz(&c, &Metadata::default());
+
+        assert_eq!(serde_json::json!(["HEART", "S"]), ipuz["solution"][0]);
+        assert_eq!(serde_json::json!(["A", "T"]), ipuz["solution"][1]);
+    }
+}
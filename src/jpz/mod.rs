@@ -0,0 +1,176 @@
+/*!
+Exports a crossword to the `.jpz` XML format of Crossword Compiler, used by many
+publications. Available with the `jpz` feature.
+*/
+
+use crate::across::AcrossFileFormat;
+use crate::crossword::Direction;
+use crate::html::escape;
+use crate::parse::{BLACK_SQUARE, VOID_SQUARE};
+use std::fmt::Write;
+
+/// Formats the puzzle as a `.jpz` document with the grid, the words and an empty clue for
+/// every word. Columns and rows are numbered from 1 as in the format, open squares are
+/// written without a solution, rebus squares with all their letters as solution and void
+/// squares as `void` cells.
+pub fn to_jpz(puzzle: &AcrossFileFormat) -> String {
+    let crossword = &puzzle.crossword;
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<crossword-compiler xmlns=\"http://crossword.info/xml/crossword-compiler\">\n");
+    xml.push_str(
+        "<rectangular-puzzle xmlns=\"http://crossword.info/xml/rectangular-puzzle\" \
+         alphabet=\"ABCDEFGHIJKLMNOPQRSTUVWXYZ\">\n",
+    );
+    writeln!(
+        xml,
+        "<metadata><title>{}</title><creator>{}</creator><copyright>{}</copyright>\
+         <description></description></metadata>",
+        escape(&puzzle.title),
+        escape(&puzzle.author),
+        escape(&puzzle.copyright)
+    )
+    .unwrap();
+    xml.push_str("<crossword>\n");
+
+    writeln!(
+        xml,
+        "<grid width=\"{}\" height=\"{}\">",
+        crossword.width, crossword.height
+    )
+    .unwrap();
+    xml.push_str("<grid-look numbering-scheme=\"normal\"/>\n");
+    for (row, col, number, c) in crossword.cells_with_numbers() {
+        write!(xml, "<cell x=\"{}\" y=\"{}\"", col + 1, row + 1).unwrap();
+        if c == VOID_SQUARE {
+            xml.push_str(" type=\"void\"/>\n");
+            continue;
+        }
+        if BLACK_SQUARE.contains(&c) {
+            xml.push_str(" type=\"block\"/>\n");
+            continue;
+        }
+        if c != ' ' {
+            let letters = crossword
+                .rebus(row, col)
+                .map_or(c.to_string(), String::from);
+            write!(xml, " solution=\"{}\"", escape(&letters)).unwrap();
+        }
+        if let Some(number) = number {
+            write!(xml, " number=\"{}\"", number).unwrap();
+        }
+        xml.push_str("/>\n");
+    }
+    xml.push_str("</grid>\n");
+
+    let entries = crossword.numbered_entries();
+    for (id, entry) in entries.iter().enumerate() {
+        let wb = &entry.word_boundary;
+        let (x, y) = match wb.direction {
+            Direction::Across => (
+                format!("{}-{}", wb.start_col + 1, wb.start_col + wb.length),
+                format!("{}", wb.start_row + 1),
+            ),
+            Direction::Down => (
+                format!("{}", wb.start_col + 1),
+                format!("{}-{}", wb.start_row + 1, wb.start_row + wb.length),
+            ),
+        };
+        writeln!(xml, "<word id=\"{}\" x=\"{}\" y=\"{}\"/>", id + 1, x, y).unwrap();
+    }
+    for (direction, title) in [(Direction::Across, "Across"), (Direction::Down, "Down")].iter() {
+        writeln!(
+            xml,
+            "<clues ordering=\"normal\"><title><b>{}</b></title>",
+            title
+        )
+        .unwrap();
+        for (id, entry) in entries.iter().enumerate() {
+            if entry.word_boundary.direction == *direction {
+                writeln!(
+                    xml,
+                    "<clue word=\"{}\" number=\"{}\"></clue>",
+                    id + 1,
+                    entry.number
+                )
+                .unwrap();
+            }
+        }
+        xml.push_str("</clues>\n");
+    }
+
+    xml.push_str("</crossword>\n</rectangular-puzzle>\n</crossword-compiler>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{across::AcrossFileFormat, crossword::Crossword};
+
+    use super::to_jpz;
+
+    #[test]
+    fn to_jpz_works() {
+        let c = Crossword::parse(String::from(
+            "
+SIAM
+N:EM
+RYAX
+",
+        ))
+        .unwrap();
+        let puzzle = AcrossFileFormat::new(
+            c,
+            String::from("Q&A <1>"),
+            String::from("author"),
+            String::from("copyright"),
+        );
+
+        let xml = to_jpz(&puzzle);
+
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let cells: Vec<roxmltree::Node> = document
+            .descendants()
+            .filter(|node| node.has_tag_name("cell"))
+            .collect();
+        assert_eq!(12, cells.len());
+        assert_eq!(Some("block"), cells[5].attribute("type"));
+        assert_eq!(Some("S"), cells[0].attribute("solution"));
+        assert_eq!(Some("1"), cells[0].attribute("number"));
+        assert_eq!(None, cells[11].attribute("solution"));
+        assert_eq!(
+            6,
+            document
+                .descendants()
+                .filter(|node| node.has_tag_name("word"))
+                .count()
+        );
+        assert_eq!(
+            Some("Q&A <1>"),
+            document
+                .descendants()
+                .find(|node| node.has_tag_name("title"))
+                .and_then(|node| node.text())
+        );
+        assert!(xml.contains("<word id=\"4\" x=\"1\" y=\"1-3\"/>"));
+        assert!(xml.contains("<clue word=\"2\" number=\"4\"></clue>"));
+    }
+
+    #[test]
+    fn rebus_squares_keep_their_letters() {
+        let c = Crossword::parse(String::from("[HEART]S\nAT")).unwrap();
+        let puzzle = AcrossFileFormat::new(c, String::new(), String::new(), String::new());
+
+        let xml = to_jpz(&puzzle);
+
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let solutions: Vec<Option<&str>> = document
+            .descendants()
+            .filter(|node| node.has_tag_name("cell"))
+            .map(|node| node.attribute("solution"))
+            .collect();
+        assert_eq!(
+            vec![Some("HEART"), Some("S"), Some("A"), Some("T")],
+            solutions
+        );
+    }
+}
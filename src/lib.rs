@@ -19,8 +19,20 @@ use std::fs::File;
 
 pub mod across;
 pub mod crossword;
+pub mod difficulty;
 pub mod fill;
+pub mod html;
+#[cfg(feature = "png")]
+pub mod image;
+pub mod ipuz;
+#[cfg(feature = "jpz")]
+pub mod jpz;
 pub mod parse;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod puz;
+pub mod svg;
+pub mod symmetry;
 pub mod trie;
 
 pub fn fill_crossword_with_default_wordlist(
@@ -30,5 +42,6 @@ pub fn fill_crossword_with_default_wordlist(
     debug: bool,
 ) -> Result<Crossword, String> {
     let trie = Trie::load_default().expect("Failed to load trie");
-    Filler::new(&trie, random, max_time_seconds, debug).fill(crossword)
+    let mut filler = Filler::new(&trie, random, max_time_seconds, debug);
+    filler.fill(crossword).map_err(String::from)
 }
@@ -2,8 +2,62 @@
 Utility methods to split a `Crossword` into component words.
 */
 use crate::{Crossword, Direction};
+use serde::{Deserialize, Serialize};
 
-const BLACK_SQUARE: [char; 2] = ['.', ':'];
+/// The characters that represent a black square.
+pub(crate) const BLACK_SQUARE: [char; 2] = ['.', ':'];
+
+/// A square outside the shape of a non-rectangular puzzle, e.g. the corners of a plus
+/// shaped grid. Void squares are neither open nor black squares: they always terminate
+/// words and exporters leave them out. `Crossword::parse` reads and `Display` writes them
+/// as spaces.
+pub(crate) const VOID_SQUARE: char = '~';
+
+/// The characters that terminate words, by default both black square characters.
+/// Void squares always terminate words.
+///
+/// A diagramless puzzle marks with `:` the black squares the solver doesn't see, which a
+/// filler may want to treat as open squares, see `Filler::with_block_chars`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BlockChars {
+    chars: Vec<char>,
+}
+
+impl BlockChars {
+    pub fn new(chars: &[char]) -> BlockChars {
+        BlockChars {
+            chars: chars.to_vec(),
+        }
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        c == VOID_SQUARE || self.chars.contains(&c)
+    }
+}
+
+impl Default for BlockChars {
+    fn default() -> BlockChars {
+        BlockChars::new(&BLACK_SQUARE)
+    }
+}
+
+/// How `Crossword::parse_with_options` reads a grid.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseOptions {
+    /// The characters of black squares, read as `.` unless they are `.` or `:`.
+    pub block_chars: BlockChars,
+    /// The character of open squares, `X` by default.
+    pub unfilled: char,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            block_chars: BlockChars::default(),
+            unfilled: 'X',
+        }
+    }
+}
 
 /// Parses a Crossword into a `Vec<WordBoundary>`. Returns all words present in the puzzle.
 ///
@@ -13,6 +67,15 @@ const BLACK_SQUARE: [char; 2] = ['.', ':'];
 ///
 /// Also note that as a `Crossword` is being filled, the word boundaries do not change.
 pub fn parse_word_boundaries(crossword: &Crossword) -> Vec<WordBoundary> {
+    parse_word_boundaries_with(crossword, &ParseOptions::default().block_chars)
+}
+
+/// Like `parse_word_boundaries`, with words terminated by the squares in `block_chars`
+/// only. Every other square is part of a word.
+pub fn parse_word_boundaries_with(
+    crossword: &Crossword,
+    block_chars: &BlockChars,
+) -> Vec<WordBoundary> {
     let mut result = vec![];
 
     let mut start_row = None;
@@ -22,16 +85,16 @@ pub fn parse_word_boundaries(crossword: &Crossword) -> Vec<WordBoundary> {
     for row in 0..crossword.height {
         for col in 0..crossword.width {
             let current_char = crossword.contents[row * crossword.width + col];
-            if !BLACK_SQUARE.contains(&current_char) {
+            if !block_chars.contains(current_char) {
                 // found a char; is it our first?
-                if start_row == None {
+                if start_row.is_none() {
                     start_row = Some(row);
                     start_col = Some(col);
                 }
                 length += 1;
             } else {
                 // If we don't have any data yet, just keep going
-                if start_row == None {
+                if start_row.is_none() {
                     continue;
                 }
                 let new_word = WordBoundary {
@@ -68,15 +131,15 @@ pub fn parse_word_boundaries(crossword: &Crossword) -> Vec<WordBoundary> {
     for col in 0..crossword.width {
         for row in 0..crossword.height {
             let current_char = crossword.contents[row * crossword.width + col];
-            if !BLACK_SQUARE.contains(&current_char) {
+            if !block_chars.contains(current_char) {
                 // found a char; is it our first?
-                if start_row == None {
+                if start_row.is_none() {
                     start_row = Some(row);
                     start_col = Some(col);
                 }
                 length += 1;
             } else {
-                if start_row == None {
+                if start_row.is_none() {
                     continue;
                 }
                 let new_word = WordBoundary {
@@ -115,7 +178,7 @@ pub fn parse_word_boundaries(crossword: &Crossword) -> Vec<WordBoundary> {
 ///
 /// Note that a `WordBoundary` can be combined with a `&Crossword` to create a `WordIterator`,
 /// which will produce the `char`s present in that specific `Crossword`.
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct WordBoundary {
     pub start_row: usize,
     pub start_col: usize,
@@ -137,17 +200,36 @@ impl WordBoundary {
             direction,
         }
     }
+
+    /// Iterates the `(row, col)` of the squares of the word, in order.
+    pub fn squares(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.length).map(move |index| match self.direction {
+            Direction::Across => (self.start_row, self.start_col + index),
+            Direction::Down => (self.start_row + index, self.start_col),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::parse::parse_word_boundaries;
+    use crate::parse::{parse_word_boundaries, parse_word_boundaries_with, BlockChars};
 
     use crate::{Crossword, Direction};
 
     use super::WordBoundary;
 
+    #[test]
+    fn squares_works() {
+        let across = WordBoundary::new(1, 2, 3, Direction::Across);
+        assert_eq!(
+            vec![(1, 2), (1, 3), (1, 4)],
+            across.squares().collect::<Vec<_>>()
+        );
+        let down = WordBoundary::new(1, 2, 2, Direction::Down);
+        assert_eq!(vec![(1, 2), (2, 2)], down.squares().collect::<Vec<_>>());
+    }
+
     #[test]
     fn parse_word_boundaries_works() {
         let c = Crossword::parse(String::from(
@@ -351,4 +433,72 @@ XXXXX.XXXX.XXXX
             }
         );
     }
+
+    #[test]
+    fn parse_word_boundaries_skips_void_squares() {
+        let c = Crossword::parse(String::from(
+            "
+ XX 
+XXXX
+XX.X
+ XX 
+",
+        ))
+            .unwrap();
+
+        let result = parse_word_boundaries(&c);
+
+        assert_eq!(
+            vec![
+                WordBoundary::new(0, 1, 2, Direction::Across),
+                WordBoundary::new(1, 0, 4, Direction::Across),
+                WordBoundary::new(2, 0, 2, Direction::Across),
+                WordBoundary::new(3, 1, 2, Direction::Across),
+                WordBoundary::new(1, 0, 2, Direction::Down),
+                WordBoundary::new(0, 1, 4, Direction::Down),
+                WordBoundary::new(0, 2, 2, Direction::Down),
+                WordBoundary::new(1, 3, 2, Direction::Down),
+            ],
+            result
+        );
+        // void squares terminate words whatever the block chars
+        assert_eq!(
+            result[0],
+            parse_word_boundaries_with(&c, &BlockChars::new(&[]))[0]
+        );
+    }
+
+    #[test]
+    fn parse_word_boundaries_with_block_chars_works() {
+        let c = Crossword::parse(String::from(
+            "
+XX:
+X.:
+XXX
+",
+        ))
+            .unwrap();
+
+        let result = parse_word_boundaries_with(&c, &BlockChars::new(&['.']));
+
+        assert_eq!(result.len(), 4);
+        assert_eq!(
+            result[0],
+            WordBoundary {
+                start_row: 0,
+                start_col: 0,
+                length: 3,
+                direction: Direction::Across
+            }
+        );
+        assert_eq!(
+            result[3],
+            WordBoundary {
+                start_row: 0,
+                start_col: 2,
+                length: 3,
+                direction: Direction::Down
+            }
+        );
+    }
 }
@@ -0,0 +1,288 @@
+/*!
+Renders a crossword as a printable PDF document, blank or solved, with an optional clue
+list below the grid. Available with the `pdf` feature.
+
+Text uses the Helvetica font every PDF reader provides, so no font is embedded.
+*/
+
+use crate::crossword::{Crossword, Direction};
+use crate::parse::{BLACK_SQUARE, VOID_SQUARE};
+use crate::svg::{LETTER_BASELINE, LETTER_SCALE, NUMBER_INSET, NUMBER_SCALE};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use std::collections::HashMap;
+
+/// The space left around the page content, in points.
+const MARGIN: f32 = 36.0;
+
+/// The largest side of a square, in points, so that small grids aren't drawn huge.
+const MAX_CELL_SIZE: f32 = 36.0;
+
+/// The font size of the clue list, in points, and the height of its lines.
+const CLUE_FONT_SIZE: f32 = 10.0;
+const CLUE_LINE_HEIGHT: f32 = 13.0;
+
+/// The average width of a Helvetica character as a fraction of the font size, to center
+/// letters in their squares and wrap clues.
+const AVERAGE_CHAR_WIDTH: f32 = 0.6;
+
+const FONT_NAME: Name = Name(b"F1");
+
+/// The size of the pages of `Crossword::to_pdf`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PageSize {
+    A4,
+    Letter,
+}
+
+impl PageSize {
+    /// The width and height of the page, in points.
+    pub fn dimensions(self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (595.0, 842.0),
+            PageSize::Letter => (612.0, 792.0),
+        }
+    }
+}
+
+/// How `Crossword::to_pdf` prints the puzzle.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PdfOptions {
+    pub page_size: PageSize,
+    /// Whether the letters of the grid are printed, for the solution, or left out, for a
+    /// blank puzzle to solve.
+    pub show_letters: bool,
+    /// The clues by direction and clue number, listed below the grid. Words without a clue
+    /// are listed with their number only.
+    pub clues: Option<HashMap<(Direction, u32), String>>,
+}
+
+impl Default for PdfOptions {
+    fn default() -> PdfOptions {
+        PdfOptions {
+            page_size: PageSize::A4,
+            show_letters: false,
+            clues: None,
+        }
+    }
+}
+
+impl Crossword {
+    /// Renders the crossword as a PDF document: the grid at the top of the first page with
+    /// the clue numbers, and the letters when `options.show_letters` is set, then the clue
+    /// list, continued on more pages if needed. Squares are sized to fit the page width, and
+    /// half of its height when there are clues. Letters outside Latin-1 print as `?`.
+    pub fn to_pdf(&self, options: PdfOptions) -> Vec<u8> {
+        let (page_width, page_height) = options.page_size.dimensions();
+        let clue_lines = options.clues.as_ref().map_or(vec![], |clues| {
+            self.clue_lines(clues, page_width - 2.0 * MARGIN)
+        });
+
+        let grid_height = if clue_lines.is_empty() {
+            page_height - 2.0 * MARGIN
+        } else {
+            page_height / 2.0 - MARGIN
+        };
+        let cell = ((page_width - 2.0 * MARGIN) / self.width as f32)
+            .min(grid_height / self.height as f32)
+            .min(MAX_CELL_SIZE);
+
+        let mut pages = vec![Content::new()];
+        self.draw_grid(&mut pages[0], cell, page_height, options.show_letters);
+
+        // the clues start below the grid and go on at the top of the next pages
+        let mut y = page_height - MARGIN - self.height as f32 * cell - 2.0 * CLUE_LINE_HEIGHT;
+        for (title, line) in clue_lines {
+            if y < MARGIN {
+                pages.push(Content::new());
+                y = page_height - MARGIN - CLUE_LINE_HEIGHT;
+            }
+            let content = pages.last_mut().unwrap();
+            let size = if title {
+                CLUE_FONT_SIZE * 1.2
+            } else {
+                CLUE_FONT_SIZE
+            };
+            draw_text(content, MARGIN, y, size, &line);
+            y -= CLUE_LINE_HEIGHT;
+        }
+
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let font_id = Ref::new(3);
+        let page_ids: Vec<Ref> = (0..pages.len())
+            .map(|index| Ref::new(4 + 2 * index as i32))
+            .collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().copied())
+            .count(pages.len() as i32);
+        pdf.type1_font(font_id)
+            .base_font(Name(b"Helvetica"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+        for (page_id, content) in page_ids.into_iter().zip(pages) {
+            let content_id = Ref::new(page_id.get() + 1);
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, page_width, page_height));
+            page.parent(page_tree_id);
+            page.contents(content_id);
+            page.resources().fonts().pair(FONT_NAME, font_id);
+            page.finish();
+            pdf.stream(content_id, &content.finish());
+        }
+        pdf.finish()
+    }
+
+    /// Draws the grid from the top left margin of the page, with squares of `cell` points.
+    fn draw_grid(&self, content: &mut Content, cell: f32, page_height: f32, show_letters: bool) {
+        content.set_line_width(0.5);
+        for (row, col, number, c) in self.cells_with_numbers() {
+            if c == VOID_SQUARE {
+                continue;
+            }
+            let x = MARGIN + col as f32 * cell;
+            let top = page_height - MARGIN - row as f32 * cell;
+            content.rect(x, top - cell, cell, cell);
+            if BLACK_SQUARE.contains(&c) {
+                content.set_fill_gray(0.0).fill_nonzero_and_stroke();
+                continue;
+            }
+            content.stroke();
+            if let Some(number) = number {
+                let size = cell * NUMBER_SCALE;
+                let x = x + cell * NUMBER_INSET + 1.0;
+                draw_text(content, x, top - size, size, &number.to_string());
+            }
+            if show_letters && c != ' ' {
+                let letters = self.rebus(row, col).map_or(c.to_string(), String::from);
+                let count = letters.chars().count() as f32;
+                let size = cell * LETTER_SCALE / count;
+                let width = count * size * AVERAGE_CHAR_WIDTH;
+                let x = x + (cell - width) / 2.0;
+                draw_text(content, x, top - cell * LETTER_BASELINE, size, &letters);
+            }
+        }
+    }
+
+    /// The lines of the clue list, each with whether it is a section title: the across
+    /// clues then the down clues, in clue order, wrapped to `width` points.
+    fn clue_lines(
+        &self,
+        clues: &HashMap<(Direction, u32), String>,
+        width: f32,
+    ) -> Vec<(bool, String)> {
+        let max_chars = (width / (CLUE_FONT_SIZE * AVERAGE_CHAR_WIDTH)) as usize;
+        let entries = self.numbered_entries();
+        let mut lines = vec![];
+        for (direction, title) in [(Direction::Across, "Across"), (Direction::Down, "Down")] {
+            if !lines.is_empty() {
+                lines.push((false, String::new()));
+            }
+            lines.push((true, title.to_string()));
+            for entry in entries
+                .iter()
+                .filter(|entry| entry.word_boundary.direction == direction)
+            {
+                let clue = clues
+                    .get(&(direction.clone(), entry.number as u32))
+                    .map_or("", String::as_str);
+                let text = format!("{}. {}", entry.number, clue);
+                lines.extend(wrap(&text, max_chars).into_iter().map(|line| (false, line)));
+            }
+        }
+        lines
+    }
+}
+
+/// Draws a line of text with its baseline starting at `x`, `y`.
+fn draw_text(content: &mut Content, x: f32, y: f32, size: f32, text: &str) {
+    content
+        .begin_text()
+        .set_fill_gray(0.0)
+        .set_font(FONT_NAME, size)
+        .next_line(x, y)
+        .show(Str(&encode(text)))
+        .end_text();
+}
+
+/// Encodes text for the WinAnsi encoding of the font, which matches Latin-1 except from
+/// 0x80 to 0x9F, where WinAnsi has other characters such as `€`. Characters in that range
+/// or outside Latin-1 are written as `?`.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x80..=0x9F => b'?',
+            code if code < 256 => code as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Splits `text` into lines of at most `max_chars` characters at spaces, continuation lines
+/// indented. Words longer than a line are left whole.
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.trim().is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(line);
+            line = String::from("   ");
+        }
+        if !line.trim().is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crossword::{Crossword, Direction};
+    use std::collections::HashMap;
+
+    use super::{PageSize, PdfOptions};
+
+    #[test]
+    fn to_pdf_works() {
+        let c = Crossword::parse(String::from("CAT\nA.X\nBE[AR]")).unwrap();
+
+        let blank = c.to_pdf(PdfOptions::default());
+        assert!(blank.starts_with(b"%PDF"));
+        assert!(blank.len() > 500);
+
+        let mut clues = HashMap::new();
+        clues.insert((Direction::Across, 1), String::from("Feline pet"));
+        clues.insert((Direction::Down, 2), "a ".repeat(500));
+        let solution = c.to_pdf(PdfOptions {
+            page_size: PageSize::Letter,
+            show_letters: true,
+            clues: Some(clues),
+        });
+        assert!(solution.starts_with(b"%PDF"));
+        assert!(solution.len() > blank.len());
+        let text = String::from_utf8_lossy(&solution);
+        assert!(text.contains("(1. Feline pet)"));
+        assert!(text.contains("/MediaBox [0 0 612 792]"));
+    }
+
+    #[test]
+    fn encode_works() {
+        assert_eq!(b"CAFE?".to_vec(), super::encode("CAFE\u{2603}"));
+        assert_eq!(
+            vec![0xC9, b'?', b'?'],
+            super::encode("\u{C9}\u{85}\u{20AC}")
+        );
+    }
+
+    #[test]
+    fn wrap_works() {
+        assert_eq!(vec!["1. A clue"], super::wrap("1. A  clue", 20));
+        assert_eq!(
+            vec!["1. A long", "   clue"],
+            super::wrap("1. A long clue", 10)
+        );
+    }
+}
@@ -0,0 +1,678 @@
+/*!
+Reads and writes the binary `.puz` format used by Across Lite and most desktop solvers.
+
+A `.puz` file holds two grids of the same shape: the solution, and the player state with
+the letters the solver has entered so far. Both are kept as a `Crossword`, with open
+squares for the squares the solver hasn't filled. The title, author, clues and other
+texts are kept in a `PuzMeta`. Text is encoded as ISO-8859-1. The rebus squares of the
+solution are stored in the `GRBS` and `RTBL` sections that follow the texts.
+See https://code.google.com/archive/p/puz/wikis/FileFormat.wiki
+*/
+
+use crate::crossword::Crossword;
+use crate::parse::{parse_word_boundaries, BLACK_SQUARE, VOID_SQUARE};
+use std::collections::BTreeMap;
+
+/// Identifies the format, two bytes into the header.
+const MAGIC: &[u8; 12] = b"ACROSS&DOWN\0";
+
+/// The length of the header, up to the solution grid.
+const HEADER_LENGTH: usize = 0x34;
+
+/// The part of the header covered by the CIB checksum.
+const CIB_RANGE: std::ops::Range<usize> = 0x2C..0x34;
+
+const VERSION: &[u8; 4] = b"1.3\0";
+
+/// A `.puz` puzzle, see the module documentation.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct Puz {
+    pub(crate) solution: Crossword,
+    pub(crate) state: Crossword,
+    pub(crate) meta: PuzMeta,
+}
+
+/// The texts of a `.puz` puzzle.
+///
+/// The clues are in the order of the format: by clue number, the across clue before the
+/// down clue of the same number, as in `Crossword::numbered_entries` sorted by number.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Default)]
+pub struct PuzMeta {
+    pub title: String,
+    pub author: String,
+    pub copyright: String,
+    pub clues: Vec<String>,
+    pub notes: String,
+}
+
+impl Puz {
+    /// Creates a puzzle from its solution and the player state, without texts. A new
+    /// puzzle starts from `solution.clear_letters()`, see `Puz::from_crossword`.
+    /// Err is returned if the grids have different black squares or contain characters
+    /// that cannot be encoded, if the solution has more than 255 different rebus squares
+    /// or if the player state has rebus squares, which are not supported.
+    pub fn new(solution: Crossword, state: Crossword) -> Result<Puz, String> {
+        if (solution.width, solution.height) != (state.width, state.height) {
+            return Err(format!(
+                "Player state of {}x{} doesn't match solution of {}x{}",
+                state.width, state.height, solution.width, solution.height
+            ));
+        }
+        let is_black = |c: &char| BLACK_SQUARE.contains(c);
+        if solution
+            .contents
+            .iter()
+            .zip(state.contents.iter())
+            .any(|(s, p)| is_black(s) != is_black(p))
+        {
+            return Err("Player state has different black squares than the solution".to_string());
+        }
+        if let Some(c) = solution
+            .contents
+            .iter()
+            .copied()
+            .chain(state.contents.iter().copied())
+            .chain(solution.rebus.values().flat_map(|letters| letters.chars()))
+            .find(|c| *c as u32 > 0xFF)
+        {
+            return Err(format!("Character `{}` cannot be encoded", c));
+        }
+        if rebus_table(&solution).len() > u8::MAX as usize {
+            return Err(format!("More than {} different rebus squares", u8::MAX));
+        }
+        if !state.rebus.is_empty() {
+            return Err("Rebus squares in the player state are not supported".to_string());
+        }
+        Ok(Puz {
+            solution,
+            state,
+            meta: PuzMeta::default(),
+        })
+    }
+
+    /// Creates a new puzzle with `crossword` as solution, an empty player state and the
+    /// texts of `meta`. Missing clues are left empty.
+    /// Err is returned if the grid or texts cannot be encoded, or there are more clues
+    /// than words in the grid.
+    pub fn from_crossword(crossword: &Crossword, meta: PuzMeta) -> Result<Puz, String> {
+        let mut puz = Puz::new(crossword.clone(), crossword.clear_letters())?;
+        let clue_count = parse_word_boundaries(crossword).len();
+        if meta.clues.len() > clue_count {
+            return Err(format!(
+                "{} clues for a grid of {} words",
+                meta.clues.len(),
+                clue_count
+            ));
+        }
+        for text in [&meta.title, &meta.author, &meta.copyright, &meta.notes]
+            .iter()
+            .copied()
+            .chain(meta.clues.iter())
+        {
+            if let Some(c) = text.chars().find(|c| *c as u32 > 0xFF || *c == '\0') {
+                return Err(format!(
+                    "Character `{}` cannot be encoded",
+                    c.escape_default()
+                ));
+            }
+        }
+        puz.meta = meta;
+        Ok(puz)
+    }
+
+    pub fn solution(&self) -> &Crossword {
+        &self.solution
+    }
+
+    /// The letters entered by the solver, with open squares for the empty ones.
+    pub fn state(&self) -> &Crossword {
+        &self.state
+    }
+
+    pub fn meta(&self) -> &PuzMeta {
+        &self.meta
+    }
+
+    /// Parses a puzzle from the contents of a `.puz` file. The rebus squares of the
+    /// solution are read from the `GRBS` and `RTBL` sections, and other sections after
+    /// the notes are skipped.
+    /// Err is returned if the contents are not an unscrambled `.puz` puzzle, the
+    /// checksums don't match or there are more clues than words in the grid.
+    pub fn parse(bytes: &[u8]) -> Result<Puz, String> {
+        // some files have a preamble before the header
+        let start = bytes
+            .windows(MAGIC.len())
+            .position(|window| window == MAGIC)
+            .filter(|position| *position >= 2)
+            .ok_or_else(|| "Not a .puz file".to_string())?
+            - 2;
+        let bytes = &bytes[start..];
+        if bytes.len() < HEADER_LENGTH {
+            return Err("Truncated .puz header".to_string());
+        }
+        let read_u16 = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let width = bytes[0x2C] as usize;
+        let height = bytes[0x2D] as usize;
+        let clue_count = read_u16(0x2E) as usize;
+        if read_u16(0x32) != 0 {
+            return Err("Scrambled .puz files are not supported".to_string());
+        }
+        if checksum(&bytes[CIB_RANGE], 0) != read_u16(0x0E) {
+            return Err("Invalid .puz header checksum".to_string());
+        }
+        let squares = width * height;
+        if squares == 0 {
+            return Err(format!("Empty {}x{} grid", width, height));
+        }
+        if bytes.len() < HEADER_LENGTH + 2 * squares {
+            return Err("Truncated .puz grids".to_string());
+        }
+
+        let mut offset = HEADER_LENGTH + 2 * squares;
+        let mut next_string = || {
+            let rest = bytes
+                .get(offset..)
+                .ok_or_else(|| "Truncated .puz texts".to_string())?;
+            let length = rest.iter().position(|b| *b == 0).unwrap_or(rest.len());
+            offset += length + 1;
+            Ok(latin1(&rest[..length]))
+        };
+        let title = next_string()?;
+        let author = next_string()?;
+        let copyright = next_string()?;
+        let clues = (0..clue_count)
+            .map(|_| next_string())
+            .collect::<Result<Vec<String>, String>>()?;
+        // older files may end without notes
+        let notes = next_string().unwrap_or_default();
+        let meta = PuzMeta {
+            title,
+            author,
+            copyright,
+            clues,
+            notes,
+        };
+
+        let solution_bytes = &bytes[HEADER_LENGTH..HEADER_LENGTH + squares];
+        let state_bytes = &bytes[HEADER_LENGTH + squares..HEADER_LENGTH + 2 * squares];
+        let with_notes = bytes[0x18..0x1C] >= VERSION[..];
+        let file_checksum = strings_checksum(
+            &meta,
+            with_notes,
+            checksum(state_bytes, checksum(solution_bytes, read_u16(0x0E))),
+        );
+        if file_checksum != read_u16(0) {
+            return Err("Invalid .puz file checksum".to_string());
+        }
+
+        let grid = |grid_bytes: &[u8]| Crossword {
+            contents: grid_bytes
+                .iter()
+                .map(|b| match b {
+                    b'-' => ' ',
+                    b => *b as char,
+                })
+                .collect(),
+            width,
+            height,
+            rebus: BTreeMap::new(),
+        };
+        let mut solution = grid(solution_bytes);
+        let sections = bytes.get(offset..).map_or(Ok(vec![]), sections)?;
+        solution.rebus = read_rebus(&sections, width, squares)?;
+        let mut puz = Puz::new(solution, grid(state_bytes))?;
+        let word_count = parse_word_boundaries(&puz.solution).len();
+        if clue_count > word_count {
+            return Err(format!(
+                "{} clues for a grid of {} words",
+                clue_count, word_count
+            ));
+        }
+        puz.meta = meta;
+        Ok(puz)
+    }
+
+    /// Formats the puzzle as the contents of a `.puz` file, with the checksums of the
+    /// format. `:` black squares and void squares, which the format lacks, are written as
+    /// `.`. Rebus squares are written with their first letter in the solution grid, and
+    /// all their letters in the `GRBS` and `RTBL` sections. Err is returned if the grid
+    /// is wider or taller than 255 squares, the most the format can store.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let (width, height) = (self.solution.width, self.solution.height);
+        if width > u8::MAX as usize || height > u8::MAX as usize {
+            return Err(format!(
+                "Grid of {}x{} exceeds the {}x{} limit of the .puz format",
+                width,
+                height,
+                u8::MAX,
+                u8::MAX
+            ));
+        }
+        let clue_count = parse_word_boundaries(&self.solution).len();
+        let solution = Puz::grid_bytes(&self.solution);
+        let state = Puz::grid_bytes(&self.state);
+
+        let mut cib = vec![width as u8, height as u8];
+        cib.extend_from_slice(&(clue_count as u16).to_le_bytes());
+        cib.extend_from_slice(&1u16.to_le_bytes());
+        cib.extend_from_slice(&0u16.to_le_bytes());
+
+        let cib_checksum = checksum(&cib, 0);
+        let masked = [
+            cib_checksum,
+            checksum(&solution, 0),
+            checksum(&state, 0),
+            strings_checksum(&self.meta, true, 0),
+        ];
+        let file_checksum = strings_checksum(
+            &self.meta,
+            true,
+            checksum(&state, checksum(&solution, cib_checksum)),
+        );
+
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH + 2 * solution.len());
+        bytes.extend_from_slice(&file_checksum.to_le_bytes());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&cib_checksum.to_le_bytes());
+        bytes.extend(
+            masked
+                .iter()
+                .zip(b"ICHE".iter())
+                .map(|(checksum, mask)| mask ^ (checksum & 0xFF) as u8),
+        );
+        bytes.extend(
+            masked
+                .iter()
+                .zip(b"ATED".iter())
+                .map(|(checksum, mask)| mask ^ (checksum >> 8) as u8),
+        );
+        bytes.extend_from_slice(VERSION);
+        bytes.extend_from_slice(&[0; 2 + 2 + 12]);
+        bytes.extend_from_slice(&cib);
+        bytes.extend_from_slice(&solution);
+        bytes.extend_from_slice(&state);
+        let clues = self.meta.clues.iter().map(String::as_str);
+        let empty_clues = std::iter::repeat_n("", clue_count - self.meta.clues.len());
+        for text in [&self.meta.title, &self.meta.author, &self.meta.copyright]
+            .iter()
+            .map(|text| text.as_str())
+            .chain(clues)
+            .chain(empty_clues)
+            .chain(std::iter::once(self.meta.notes.as_str()))
+        {
+            bytes.extend(text.chars().map(|c| c as u8));
+            bytes.push(0);
+        }
+
+        let table = rebus_table(&self.solution);
+        if !table.is_empty() {
+            let grbs: Vec<u8> = (0..width * height)
+                .map(|square| {
+                    let letters = self.solution.rebus(square / width, square % width);
+                    letters.map_or(0, |letters| {
+                        table.iter().position(|l| *l == letters).unwrap() as u8 + 1
+                    })
+                })
+                .collect();
+            let rtbl: String = table
+                .iter()
+                .enumerate()
+                .map(|(index, letters)| format!("{:>2}:{};", index, letters))
+                .collect();
+            write_section(&mut bytes, b"GRBS", &grbs);
+            write_section(
+                &mut bytes,
+                b"RTBL",
+                &rtbl.chars().map(|c| c as u8).collect::<Vec<u8>>(),
+            );
+        }
+        Ok(bytes)
+    }
+
+    fn grid_bytes(crossword: &Crossword) -> Vec<u8> {
+        crossword
+            .contents
+            .iter()
+            .map(|c| match c {
+                ' ' => b'-',
+                c if BLACK_SQUARE.contains(c) || *c == VOID_SQUARE => b'.',
+                c => *c as u8,
+            })
+            .collect()
+    }
+}
+
+/// The checksum of the format over `bytes`, continuing from `checksum`.
+fn checksum(bytes: &[u8], checksum: u16) -> u16 {
+    bytes.iter().fold(checksum, |checksum, b| {
+        checksum.rotate_right(1).wrapping_add(*b as u16)
+    })
+}
+
+/// The checksum of the format over the texts, continuing from `checksum`. Empty texts
+/// don't count, and clues count without their terminating NUL.
+fn strings_checksum(meta: &PuzMeta, with_notes: bool, checksum_so_far: u16) -> u16 {
+    let with_nul = |text: &str| {
+        let mut bytes: Vec<u8> = text.chars().map(|c| c as u8).collect();
+        bytes.push(0);
+        bytes
+    };
+    let mut result = checksum_so_far;
+    for text in [&meta.title, &meta.author, &meta.copyright].iter() {
+        if !text.is_empty() {
+            result = checksum(&with_nul(text), result);
+        }
+    }
+    for clue in meta.clues.iter() {
+        let bytes: Vec<u8> = clue.chars().map(|c| c as u8).collect();
+        result = checksum(&bytes, result);
+    }
+    if with_notes && !meta.notes.is_empty() {
+        result = checksum(&with_nul(&meta.notes), result);
+    }
+    result
+}
+
+/// The different letters of the rebus squares of `crossword`, in the order of the squares.
+fn rebus_table(crossword: &Crossword) -> Vec<&str> {
+    let mut table: Vec<&str> = vec![];
+    for letters in crossword.rebus.values() {
+        if !table.contains(&letters.as_str()) {
+            table.push(letters);
+        }
+    }
+    table
+}
+
+/// Appends an extra section: its title, the length and checksum of its data, the data and
+/// a terminating NUL.
+fn write_section(bytes: &mut Vec<u8>, title: &[u8; 4], data: &[u8]) {
+    bytes.extend_from_slice(title);
+    bytes.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&checksum(data, 0).to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes.push(0);
+}
+
+/// The title and data of an extra section.
+type Section<'a> = (&'a [u8], &'a [u8]);
+
+/// Splits the extra sections following the texts into their titles and data.
+fn sections(mut bytes: &[u8]) -> Result<Vec<Section<'_>>, String> {
+    let mut sections = vec![];
+    while bytes.len() >= 8 {
+        let title = &bytes[..4];
+        let length = u16::from_le_bytes([bytes[4], bytes[5]]) as usize;
+        let data = bytes
+            .get(8..8 + length)
+            .ok_or_else(|| format!("Truncated .puz {} section", latin1(title)))?;
+        if checksum(data, 0) != u16::from_le_bytes([bytes[6], bytes[7]]) {
+            return Err(format!("Invalid .puz {} section checksum", latin1(title)));
+        }
+        sections.push((title, data));
+        bytes = bytes.get(9 + length..).unwrap_or_default();
+    }
+    Ok(sections)
+}
+
+/// Reads the rebus squares of a grid of `squares` squares from its `GRBS` and `RTBL`
+/// sections, by row and column.
+fn read_rebus(
+    sections: &[Section],
+    width: usize,
+    squares: usize,
+) -> Result<BTreeMap<(usize, usize), String>, String> {
+    let section = |title: &[u8]| {
+        sections
+            .iter()
+            .find(|(t, _)| *t == title)
+            .map(|(_, data)| *data)
+    };
+    let (grbs, rtbl) = match (section(b"GRBS"), section(b"RTBL")) {
+        (Some(grbs), Some(rtbl)) => (grbs, rtbl),
+        _ => return Ok(BTreeMap::new()),
+    };
+    if grbs.len() != squares {
+        return Err("Invalid .puz GRBS section".to_string());
+    }
+    let mut table = BTreeMap::new();
+    for entry in latin1(rtbl).split(';').filter(|entry| !entry.is_empty()) {
+        let (index, letters) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid .puz RTBL entry: {}", entry))?;
+        let index: u8 = index
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid .puz RTBL entry: {}", entry))?;
+        table.insert(index, letters.to_string());
+    }
+    let mut rebus = BTreeMap::new();
+    for (square, b) in grbs.iter().enumerate().filter(|(_, b)| **b != 0) {
+        let letters = table
+            .get(&(b - 1))
+            .ok_or_else(|| format!("Missing .puz RTBL entry: {}", b - 1))?;
+        // a single letter is a regular square, as in `Crossword::set_rebus`
+        if letters.chars().count() > 1 {
+            rebus.insert((square / width, square % width), letters.clone());
+        }
+    }
+    Ok(rebus)
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crossword::Crossword;
+
+    use super::{checksum, strings_checksum, Puz, PuzMeta, CIB_RANGE, HEADER_LENGTH};
+
+    #[test]
+    fn parse_works() {
+        let bytes = std::fs::read("grids/partial.puz").unwrap();
+
+        let puz = Puz::parse(&bytes).unwrap();
+
+        assert_eq!("CAT\nA.O\nBOW", puz.solution().to_string());
+        assert_eq!("CXT\nX.O\nBXX", puz.state().to_string());
+        assert_eq!(
+            &PuzMeta {
+                title: String::from("Partial progress"),
+                author: String::from("xwords"),
+                copyright: String::new(),
+                clues: vec![
+                    String::from("Feline"),
+                    String::from("Taxi"),
+                    String::from("Pull along"),
+                    String::from("Ribbon knot"),
+                ],
+                notes: String::new(),
+            },
+            puz.meta()
+        );
+    }
+
+    #[test]
+    fn to_bytes_matches_fixture() {
+        let bytes = std::fs::read("grids/partial.puz").unwrap();
+        let puz = Puz::parse(&bytes).unwrap();
+
+        assert_eq!(bytes, puz.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_checksums() {
+        let mut bytes = std::fs::read("grids/partial.puz").unwrap();
+        bytes[0x40] = b'Z';
+        assert_eq!(
+            Err(String::from("Invalid .puz file checksum")),
+            Puz::parse(&bytes)
+        );
+        bytes[0x2E] = 5;
+        assert_eq!(
+            Err(String::from("Invalid .puz header checksum")),
+            Puz::parse(&bytes)
+        );
+    }
+
+    #[test]
+    fn round_trip_works() {
+        let solution = Crossword::parse(String::from(
+            "
+SIAM
+N.EM
+RYAL
+",
+        ))
+        .unwrap();
+        let mut state = solution.clear_letters();
+        state.set_char(2, 1, 'Y').unwrap();
+
+        let puz = Puz::new(solution.clone(), state).unwrap();
+        let parsed = Puz::parse(&puz.to_bytes().unwrap()).unwrap();
+        assert_eq!(puz.solution(), parsed.solution());
+        assert_eq!(puz.state(), parsed.state());
+        assert_eq!(vec![String::new(); 6], parsed.meta().clues);
+
+        let meta = PuzMeta {
+            title: String::from("Siam"),
+            author: String::from("Café"),
+            clues: vec![String::from("Old Thailand"), String::from("Printer's unit")],
+            notes: String::from("A note"),
+            ..PuzMeta::default()
+        };
+        let puz = Puz::from_crossword(&solution, meta).unwrap();
+        let parsed = Puz::parse(&puz.to_bytes().unwrap()).unwrap();
+        assert_eq!(&solution, parsed.solution());
+        assert_eq!(solution.clear_letters(), *parsed.state());
+        assert_eq!(puz.meta().title, parsed.meta().title);
+        assert_eq!(puz.meta().notes, parsed.meta().notes);
+        assert_eq!(6, parsed.meta().clues.len());
+        assert_eq!(puz.meta().clues[..], parsed.meta().clues[..2]);
+    }
+
+    #[test]
+    fn rebus_squares_round_trip() {
+        let solution = Crossword::parse(String::from("[HEART]S.\nAT[HEART]\n[TEN]OE")).unwrap();
+
+        let puz = Puz::from_crossword(&solution, PuzMeta::default()).unwrap();
+        let bytes = puz.to_bytes().unwrap();
+
+        let rtbl = bytes.windows(4).position(|w| w == b"RTBL").unwrap();
+        assert_eq!(&[16, 0], &bytes[rtbl + 4..rtbl + 6]);
+        assert_eq!(b" 0:HEART; 1:TEN;\0", &bytes[rtbl + 8..]);
+        let parsed = Puz::parse(&bytes).unwrap();
+        assert_eq!(&solution, parsed.solution());
+        assert_eq!(Some("TEN"), parsed.solution().rebus(2, 0));
+        assert_eq!(puz.state(), parsed.state());
+
+        let mut state = solution.clear_letters();
+        state.set_rebus(0, 1, "ST").unwrap();
+        assert_eq!(
+            Err(String::from(
+                "Rebus squares in the player state are not supported"
+            )),
+            Puz::new(solution, state)
+        );
+    }
+
+    #[test]
+    fn parse_rejects_extra_clues() {
+        let solution = Crossword::parse(String::from("SIAM\nN.EM\nRYAL")).unwrap();
+        let meta = PuzMeta {
+            clues: vec![String::from("Clue"); 6],
+            ..PuzMeta::default()
+        };
+        let puz = Puz::from_crossword(&solution, meta).unwrap();
+        let mut bytes = puz.to_bytes().unwrap();
+
+        // black out the last row of both grids, leaving fewer words than clues
+        bytes[HEADER_LENGTH + 8..HEADER_LENGTH + 12].copy_from_slice(b"....");
+        bytes[HEADER_LENGTH + 20..HEADER_LENGTH + 24].copy_from_slice(b"....");
+        let grids = checksum(
+            &bytes[HEADER_LENGTH + 12..HEADER_LENGTH + 24],
+            checksum(
+                &bytes[HEADER_LENGTH..HEADER_LENGTH + 12],
+                checksum(&bytes[CIB_RANGE], 0),
+            ),
+        );
+        let file_checksum = strings_checksum(puz.meta(), true, grids);
+        bytes[0..2].copy_from_slice(&file_checksum.to_le_bytes());
+
+        assert_eq!(
+            Err(String::from("6 clues for a grid of 5 words")),
+            Puz::parse(&bytes)
+        );
+    }
+
+    #[test]
+    fn grid_files_round_trip() {
+        for name in ["20201005_empty", "20201124_empty", "code_project", "waffle"].iter() {
+            let crossword = Crossword::parse_from_file(format!("grids/{}.txt", name)).unwrap();
+            let meta = PuzMeta {
+                title: name.to_string(),
+                ..PuzMeta::default()
+            };
+
+            let puz = Puz::from_crossword(&crossword, meta).unwrap();
+            let parsed = Puz::parse(&puz.to_bytes().unwrap()).unwrap();
+
+            assert_eq!(&crossword, parsed.solution());
+            assert_eq!(name, &parsed.meta().title);
+        }
+    }
+
+    #[test]
+    fn invalid_puzzles_are_rejected() {
+        let solution = Crossword::parse(String::from("AB\nC.")).unwrap();
+
+        assert!(Puz::new(
+            solution.clone(),
+            Crossword::parse(String::from("XX\nXX")).unwrap()
+        )
+        .is_err());
+        assert!(Puz::new(
+            solution.clone(),
+            Crossword::parse(String::from("XXX\nXX.")).unwrap()
+        )
+        .is_err());
+        assert!(Puz::new(
+            solution.clone(),
+            Crossword::parse(String::from("XŞ\nX.")).unwrap()
+        )
+        .is_err());
+        let too_many_clues = PuzMeta {
+            clues: vec![String::new(); 3],
+            ..PuzMeta::default()
+        };
+        assert!(Puz::from_crossword(&solution, too_many_clues).is_err());
+        let unencodable = PuzMeta {
+            title: String::from("Ş"),
+            ..PuzMeta::default()
+        };
+        assert!(Puz::from_crossword(&solution, unencodable).is_err());
+        assert!(Puz::parse(b"not a puz file").is_err());
+    }
+
+    #[test]
+    fn oversized_grids_are_rejected() {
+        let row = "A".repeat(256);
+        let wide = Crossword::parse(format!("{}\n{}", row, row)).unwrap();
+        let puz = Puz::from_crossword(&wide, PuzMeta::default()).unwrap();
+
+        assert_eq!(
+            Err(String::from(
+                "Grid of 256x2 exceeds the 255x255 limit of the .puz format"
+            )),
+            puz.to_bytes()
+        );
+
+        let row = "A".repeat(255);
+        let widest = Crossword::parse(format!("{}\n{}", row, row)).unwrap();
+        let puz = Puz::from_crossword(&widest, PuzMeta::default()).unwrap();
+        let parsed = Puz::parse(&puz.to_bytes().unwrap()).unwrap();
+        assert_eq!(&widest, parsed.solution());
+    }
+}
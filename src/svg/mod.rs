@@ -0,0 +1,185 @@
+/*!
+Renders a crossword as a self-contained SVG image, e.g. for share images. No fonts or
+stylesheets are embedded: text uses a font family installed where the image is shown.
+*/
+
+use crate::crossword::Crossword;
+use crate::html::escape;
+use crate::parse::{WordBoundary, BLACK_SQUARE, VOID_SQUARE};
+use std::fmt::Write;
+
+/// The fill of the squares of `SvgOptions::highlight`.
+const HIGHLIGHT_FILL: &str = "#ffd966";
+
+/// The font size of clue numbers as a fraction of the side of a square. Numbers sit in the
+/// top left corner, `NUMBER_INSET` of the side and a point from the left edge.
+pub(crate) const NUMBER_SCALE: f32 = 0.3;
+pub(crate) const NUMBER_INSET: f32 = 1.0 / 16.0;
+
+/// The font size of letters as a fraction of the side of a square, shared by the letters of
+/// rebus squares, and the height of their baseline below the top edge.
+pub(crate) const LETTER_SCALE: f32 = 0.6;
+pub(crate) const LETTER_BASELINE: f32 = 0.85;
+
+/// How `Crossword::to_svg` draws the grid.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SvgOptions {
+    /// The side of a square, in pixels.
+    pub cell_size: usize,
+    /// Whether the letters of the grid are drawn, e.g. to share a solution.
+    pub show_letters: bool,
+    /// Whether clue numbers are drawn in the top left corner of the squares.
+    pub show_numbers: bool,
+    /// The CSS font family of letters and numbers.
+    pub font_family: String,
+    /// A slot whose squares are shaded, such as the word of the day.
+    pub highlight: Option<WordBoundary>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions {
+            cell_size: 32,
+            show_letters: false,
+            show_numbers: true,
+            font_family: String::from("sans-serif"),
+            highlight: None,
+        }
+    }
+}
+
+impl Crossword {
+    /// Renders the crossword as an SVG document with one `<rect>` per square: black squares
+    /// are filled black, other squares are white with a black outline and the squares of
+    /// the highlighted slot are shaded. Void squares are left out. Letters, the letters of
+    /// rebus squares included, and clue numbers are drawn as `<text>` as set in `options`.
+    pub fn to_svg(&self, options: SvgOptions) -> String {
+        let size = options.cell_size;
+        let scaled = |fraction: f32| (size as f32 * fraction) as usize;
+        let highlighted: Vec<(usize, usize)> = options
+            .highlight
+            .as_ref()
+            .map(|wb| wb.squares().collect())
+            .unwrap_or_default();
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+             viewBox=\"0 0 {0} {1}\">",
+            self.width * size,
+            self.height * size
+        )
+        .unwrap();
+        let font_family = escape(&options.font_family);
+        for (row, col, number, c) in self.cells_with_numbers() {
+            if c == VOID_SQUARE {
+                continue;
+            }
+            let fill = if BLACK_SQUARE.contains(&c) {
+                "black"
+            } else if highlighted.contains(&(row, col)) {
+                HIGHLIGHT_FILL
+            } else {
+                "white"
+            };
+            writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"{3}\" \
+                 stroke=\"black\"/>",
+                col * size,
+                row * size,
+                size,
+                fill
+            )
+            .unwrap();
+            if BLACK_SQUARE.contains(&c) {
+                continue;
+            }
+            if let (true, Some(number)) = (options.show_numbers, number) {
+                writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\">{}</text>",
+                    col * size + scaled(NUMBER_INSET) + 1,
+                    row * size + scaled(NUMBER_SCALE),
+                    font_family,
+                    scaled(NUMBER_SCALE),
+                    number
+                )
+                .unwrap();
+            }
+            if options.show_letters && c != ' ' {
+                let letters = self.rebus(row, col).map_or(c.to_string(), String::from);
+                writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" \
+                     text-anchor=\"middle\">{}</text>",
+                    col * size + size / 2,
+                    row * size + scaled(LETTER_BASELINE),
+                    font_family,
+                    scaled(LETTER_SCALE) / letters.chars().count(),
+                    escape(&letters)
+                )
+                .unwrap();
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        crossword::{Crossword, Direction},
+        parse::WordBoundary,
+    };
+
+    use super::{SvgOptions, HIGHLIGHT_FILL};
+
+    #[test]
+    fn to_svg_works() {
+        let c = Crossword::parse(String::from("CAT\nA.X\nBE[AR]")).unwrap();
+
+        let svg = c.to_svg(SvgOptions::default());
+
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"96\" height=\"96\"")
+        );
+        assert_eq!(9, svg.matches("<rect").count());
+        assert_eq!(1, svg.matches("fill=\"black\"").count());
+        assert_eq!(3, svg.matches("<text").count());
+        assert!(svg.contains(">1</text>"));
+        assert!(!svg.contains(">CAT<") && !svg.contains(">C</text>"));
+
+        let solution = c.to_svg(SvgOptions {
+            show_letters: true,
+            show_numbers: false,
+            font_family: String::from("Georgia"),
+            ..SvgOptions::default()
+        });
+        assert_eq!(7, solution.matches("<text").count());
+        assert!(solution.contains("font-family=\"Georgia\""));
+        assert!(solution.contains(">C</text>"));
+        assert!(solution.contains(">AR</text>"));
+        assert!(!solution.contains(">1</text>"));
+    }
+
+    #[test]
+    fn highlight_works() {
+        let c = Crossword::parse(String::from("ABC\nD.E\nFGH")).unwrap();
+
+        let plain = c.to_svg(SvgOptions::default());
+        let highlighted = c.to_svg(SvgOptions {
+            highlight: Some(WordBoundary::new(0, 2, 3, Direction::Down)),
+            ..SvgOptions::default()
+        });
+
+        assert_eq!(0, plain.matches(HIGHLIGHT_FILL).count());
+        assert_eq!(3, highlighted.matches(HIGHLIGHT_FILL).count());
+        assert!(highlighted.contains(&format!(
+            "<rect x=\"64\" y=\"32\" width=\"32\" height=\"32\" fill=\"{}\"",
+            HIGHLIGHT_FILL
+        )));
+        assert_eq!(1, highlighted.matches("fill=\"black\"").count());
+    }
+}
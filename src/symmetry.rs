@@ -0,0 +1,270 @@
+/*!
+Checks for the symmetry of the black squares of a crossword.
+
+Only the pattern of black squares is considered; letters are ignored. Void squares,
+outside the shape of a non-rectangular grid, pair like black squares. Note that
+transforms such as `Crossword::crop` and `Crossword::pad` can break the symmetry
+of a grid, so check it again on the transformed grid when it matters.
+*/
+
+use crate::{
+    crossword::Crossword,
+    parse::{BLACK_SQUARE, VOID_SQUARE},
+};
+
+/// A kind of symmetry of the black squares of a grid.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Symmetry {
+    /// The grid looks the same after a half turn. This is the standard for American crosswords.
+    Rotational,
+    /// The grid looks the same when mirrored left to right.
+    MirrorLeftRight,
+    /// The grid looks the same when mirrored top to bottom.
+    MirrorTopBottom,
+}
+
+impl Symmetry {
+    /// Returns the square that must match `(row, col)` under this symmetry.
+    pub fn partner(&self, crossword: &Crossword, row: usize, col: usize) -> (usize, usize) {
+        match self {
+            Symmetry::Rotational => (crossword.height - 1 - row, crossword.width - 1 - col),
+            Symmetry::MirrorLeftRight => (row, crossword.width - 1 - col),
+            Symmetry::MirrorTopBottom => (crossword.height - 1 - row, col),
+        }
+    }
+}
+
+impl Crossword {
+    /// Whether the black squares of the grid have the given symmetry.
+    pub fn has_symmetry(&self, symmetry: Symmetry) -> bool {
+        (0..self.height).all(|row| {
+            (0..self.width).all(|col| {
+                let (partner_row, partner_col) = symmetry.partner(self, row, col);
+                self.is_blocked(row, col) == self.is_blocked(partner_row, partner_col)
+            })
+        })
+    }
+
+    /// Whether the black squares of the grid look the same after a half turn, as standard
+    /// American crosswords require.
+    pub fn is_rotationally_symmetric(&self) -> bool {
+        self.has_symmetry(Symmetry::Rotational)
+    }
+
+    /// Returns the `(row, col)` of every square that breaks rotational symmetry, in reading
+    /// order: the black or void squares whose partner is neither, and their partners.
+    pub fn symmetry_violations(&self) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .filter(|(row, col)| {
+                let (partner_row, partner_col) = Symmetry::Rotational.partner(self, *row, *col);
+                self.is_blocked(*row, *col) != self.is_blocked(partner_row, partner_col)
+            })
+            .collect()
+    }
+
+    /// Returns the symmetry of the black squares of the grid, or `None` if it has none.
+    /// When a grid has several symmetries, rotational symmetry is preferred, then
+    /// left to right mirroring.
+    pub fn symmetry(&self) -> Option<Symmetry> {
+        [
+            Symmetry::Rotational,
+            Symmetry::MirrorLeftRight,
+            Symmetry::MirrorTopBottom,
+        ]
+        .iter()
+        .copied()
+        .find(|symmetry| self.has_symmetry(*symmetry))
+    }
+
+    /// Returns the grid with the given symmetry, made by adding the partner of every
+    /// unpaired black or void square as a black square. Black and void squares are never
+    /// changed, and letters on squares that must become black are lost.
+    pub fn enforce_symmetry(&self, symmetry: Symmetry) -> Crossword {
+        let mut result = self.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.is_blocked(row, col) {
+                    let (partner_row, partner_col) = symmetry.partner(self, row, col);
+                    if !result.is_blocked(partner_row, partner_col) {
+                        result.contents[partner_row * self.width + partner_col] =
+                            match self.contents[row * self.width + col] {
+                                VOID_SQUARE => BLACK_SQUARE[0],
+                                c => c,
+                            };
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    pub(crate) fn is_black(&self, row: usize, col: usize) -> bool {
+        BLACK_SQUARE.contains(&self.contents[row * self.width + col])
+    }
+
+    /// Whether the square is black or void, the squares that pair under a symmetry.
+    fn is_blocked(&self, row: usize, col: usize) -> bool {
+        self.is_black(row, col) || self.contents[row * self.width + col] == VOID_SQUARE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crossword::Crossword;
+
+    use super::Symmetry;
+
+    #[test]
+    fn symmetry_works() {
+        let rotational = Crossword::parse(String::from(
+            "
+XX.
+XXX
+.XX
+",
+        ))
+        .unwrap();
+        assert_eq!(Some(Symmetry::Rotational), rotational.symmetry());
+        assert!(!rotational.has_symmetry(Symmetry::MirrorLeftRight));
+
+        let mirror = Crossword::parse(String::from(
+            "
+.X.
+XXX
+XXX
+",
+        ))
+        .unwrap();
+        assert_eq!(Some(Symmetry::MirrorLeftRight), mirror.symmetry());
+
+        let mirror = Crossword::parse(String::from(
+            "
+.XX
+XXX
+:XX
+",
+        ))
+        .unwrap();
+        assert_eq!(Some(Symmetry::MirrorTopBottom), mirror.symmetry());
+
+        let none = Crossword::parse(String::from(
+            "
+.XX
+XXX
+XXX
+",
+        ))
+        .unwrap();
+        assert_eq!(None, none.symmetry());
+    }
+
+    #[test]
+    fn enforce_symmetry_works() {
+        let asymmetric = Crossword::parse(String::from(
+            "
+.XXX
+XXXX
+XX:X
+AXXX
+",
+        ))
+        .unwrap();
+        assert!(!asymmetric.has_symmetry(Symmetry::Rotational));
+
+        let symmetric = asymmetric.enforce_symmetry(Symmetry::Rotational);
+        assert!(symmetric.has_symmetry(Symmetry::Rotational));
+        assert_eq!(".XXX\nX:XX\nXX:X\nAXX.", symmetric.to_string());
+        // only blocks were added
+        for (before, after) in asymmetric.contents.iter().zip(symmetric.contents.iter()) {
+            assert!(before == after || after == &'.' || after == &':');
+        }
+
+        let mirrored = asymmetric.enforce_symmetry(Symmetry::MirrorLeftRight);
+        assert_eq!(".XX.\nXXXX\nX::X\nAXXX", mirrored.to_string());
+        assert_eq!(symmetric, symmetric.enforce_symmetry(Symmetry::Rotational));
+    }
+
+    #[test]
+    fn void_squares_pair_like_blocks() {
+        // a non-rectangular grid, with void squares cut out of two corners
+        let shaped = Crossword::parse(String::from(
+            "
+ XXX
+XXXX
+XXXX
+XXX 
+",
+        ))
+        .unwrap();
+        assert_eq!(Some(Symmetry::Rotational), shaped.symmetry());
+        assert!(shaped.symmetry_violations().is_empty());
+
+        let asymmetric = Crossword::parse(String::from(
+            "
+ XXX
+XXXX
+XXXX
+XXXX
+",
+        ))
+        .unwrap();
+        assert!(!asymmetric.is_rotationally_symmetric());
+        assert_eq!(vec![(0, 0), (3, 3)], asymmetric.symmetry_violations());
+
+        // the partner of a void square becomes black, and void squares are left alone
+        let symmetric = asymmetric.enforce_symmetry(Symmetry::Rotational);
+        assert_eq!(" XXX\nXXXX\nXXXX\nXXX.", symmetric.to_string());
+        let mirrored = Crossword::parse(String::from(" XX.\nXXXX\nXXXX\nXXXX")).unwrap();
+        assert_eq!(
+            mirrored,
+            asymmetric.enforce_symmetry(Symmetry::MirrorLeftRight)
+        );
+        let blocked = Crossword::parse(String::from(".XXX\nXXXX\nXXXX\nXXX ")).unwrap();
+        assert!(blocked.is_rotationally_symmetric());
+        assert_eq!(blocked, blocked.enforce_symmetry(Symmetry::Rotational));
+    }
+
+    #[test]
+    fn symmetry_violations_works() {
+        let symmetric = Crossword::parse_from_file("grids/20201012_empty.txt").unwrap();
+        assert!(symmetric.is_rotationally_symmetric());
+        assert!(symmetric.symmetry_violations().is_empty());
+
+        // `.` and `:` are both black
+        let mixed = Crossword::parse(String::from(
+            "
+.XX
+XXX
+XX:
+",
+        ))
+        .unwrap();
+        assert!(mixed.is_rotationally_symmetric());
+
+        let asymmetric = Crossword::parse(String::from(
+            "
+.XXX
+XXXX
+XX:X
+XXX.
+",
+        ))
+        .unwrap();
+        assert!(!asymmetric.is_rotationally_symmetric());
+        assert_eq!(vec![(1, 1), (2, 2)], asymmetric.symmetry_violations());
+    }
+
+    #[test]
+    fn crop_can_break_symmetry() {
+        let c = Crossword::parse_from_file("grids/20201012_empty.txt").unwrap();
+        assert_eq!(Some(Symmetry::Rotational), c.symmetry());
+
+        let cropped = c.crop(0, 0, 5, 5).unwrap();
+        assert_eq!(None, cropped.symmetry());
+
+        let padded = c.pad(1, 0, 0, 0);
+        assert_eq!(None, padded.symmetry());
+        assert_eq!(Some(Symmetry::Rotational), c.pad(1, 1, 1, 1).symmetry());
+    }
+}
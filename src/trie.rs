@@ -3,12 +3,17 @@ A data structure that provides efficient lookup of partially filled words.
 */
 
 use crate::File;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::io::ErrorKind::InvalidInput;
 use std::io::{BufRead, Error};
-use std::path::PathBuf;
-use std::{fmt, io};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{fmt, fs, io, mem};
+
+/// The number of edits between a misspelled word and the suggestions of `Trie::spellcheck`.
+const SPELLCHECK_MAX_DISTANCE: usize = 2;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TrieNode {
@@ -42,6 +47,36 @@ impl TrieNode {
         self
     }
 
+    /// Removes the word spelled by `chars` below this node, pruning the nodes left
+    /// without words. Returns whether the word was present.
+    fn remove(&mut self, chars: &[char]) -> bool {
+        let (first, rest) = match chars.split_first() {
+            Some(split) => split,
+            None => {
+                let present = self.is_terminal;
+                self.is_terminal = false;
+                return present;
+            }
+        };
+        let child = match self.children.get_mut(first) {
+            Some(child) => child,
+            None => return false,
+        };
+        let present = child.remove(rest);
+        if !child.is_terminal && child.children.is_empty() {
+            self.children.remove(first);
+        }
+        present
+    }
+
+    fn find(&self, word: &str) -> Option<&TrieNode> {
+        let mut node = self;
+        for c in word.chars() {
+            node = node.children.get(&c)?;
+        }
+        Some(node)
+    }
+
     fn display_helper(
         &self,
         f: &mut fmt::Formatter<'_>,
@@ -65,7 +100,7 @@ impl TrieNode {
             return writeln!(f);
         }
 
-        for (index, key) in self.children.keys().into_iter().enumerate() {
+        for (index, key) in self.children.keys().enumerate() {
             self.children
                 .get(key)
                 .unwrap()
@@ -81,8 +116,8 @@ impl TrieNode {
         partial: &mut String,
         result: &mut Vec<String>,
     ) {
-        if self.contents.is_some() {
-            partial.push(self.contents.unwrap());
+        if let Some(c) = self.contents {
+            partial.push(c);
         }
 
         match pattern.next() {
@@ -109,6 +144,207 @@ impl TrieNode {
         }
     }
 
+    fn words_with_letters<T: Iterator<Item = char> + Clone>(
+        &self,
+        mut pattern: T,
+        letters: &[Option<FxHashSet<char>>],
+        partial: &mut String,
+        result: &mut Vec<String>,
+    ) {
+        if let Some(c) = self.contents {
+            partial.push(c);
+        }
+
+        match pattern.next() {
+            Some(new_char) => {
+                let (allowed, rest) = match letters.split_first() {
+                    Some((allowed, rest)) => (allowed.as_ref(), rest),
+                    None => (None, letters),
+                };
+                if new_char == ' ' {
+                    for (c, child) in self.children.iter() {
+                        if allowed.is_none_or(|allowed| allowed.contains(c)) {
+                            child.words_with_letters(pattern.clone(), rest, partial, result);
+                        }
+                    }
+                } else if let Some(child) = self.children.get(&new_char) {
+                    child.words_with_letters(pattern, rest, partial, result);
+                }
+            }
+            None => {
+                if self.is_terminal {
+                    result.push(partial.clone());
+                }
+            }
+        }
+
+        if self.contents.is_some() {
+            partial.pop();
+        }
+    }
+
+    /// Counts the words matching `pattern` below this node, stopping once `limit` is reached.
+    fn count_words<T: Iterator<Item = char> + Clone>(&self, mut pattern: T, limit: usize) -> usize {
+        match pattern.next() {
+            Some(' ') => {
+                let mut count = 0;
+                for child in self.children.values() {
+                    if count >= limit {
+                        break;
+                    }
+                    count += child.count_words(pattern.clone(), limit - count);
+                }
+                count
+            }
+            Some(c) => self
+                .children
+                .get(&c)
+                .map_or(0, |child| child.count_words(pattern, limit)),
+            None => usize::from(self.is_terminal),
+        }
+    }
+
+    /// Walks `this` and `other` together, collecting the words terminal in only one of them.
+    /// Either node may be missing, in which case all words below the other one are collected.
+    fn diff(
+        this: Option<&TrieNode>,
+        other: Option<&TrieNode>,
+        partial: &mut String,
+        only_this: &mut Vec<String>,
+        only_other: &mut Vec<String>,
+    ) {
+        let this_terminal = this.is_some_and(|node| node.is_terminal);
+        let other_terminal = other.is_some_and(|node| node.is_terminal);
+        if this_terminal && !other_terminal {
+            only_this.push(partial.clone());
+        } else if other_terminal && !this_terminal {
+            only_other.push(partial.clone());
+        }
+
+        let mut keys: Vec<char> = this
+            .into_iter()
+            .chain(other)
+            .flat_map(|node| node.children.keys().copied())
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        for key in keys {
+            partial.push(key);
+            TrieNode::diff(
+                this.and_then(|node| node.children.get(&key)),
+                other.and_then(|node| node.children.get(&key)),
+                partial,
+                only_this,
+                only_other,
+            );
+            partial.pop();
+        }
+    }
+
+    /// Collects the words below this node within `max_distance` edits of `target`, with
+    /// their distance. `previous_row` holds the edit distances between `partial` and each
+    /// prefix of `target`, as in the Levenshtein dynamic programming table.
+    fn fuzzy(
+        &self,
+        target: &[char],
+        previous_row: &[usize],
+        max_distance: usize,
+        partial: &mut String,
+        result: &mut Vec<(usize, String)>,
+    ) {
+        for (c, child) in self.children.iter() {
+            let mut row = Vec::with_capacity(previous_row.len());
+            row.push(previous_row[0] + 1);
+            for (index, target_char) in target.iter().enumerate() {
+                let substitution = previous_row[index] + usize::from(target_char != c);
+                let insertion = row[index] + 1;
+                let deletion = previous_row[index + 1] + 1;
+                row.push(substitution.min(insertion).min(deletion));
+            }
+            partial.push(*c);
+            let distance = row[target.len()];
+            if child.is_terminal && distance <= max_distance {
+                result.push((distance, partial.clone()));
+            }
+            // the distance can only grow along the branch once every cell exceeds the limit
+            if row.iter().any(|distance| *distance <= max_distance) {
+                child.fuzzy(target, &row, max_distance, partial, result);
+            }
+            partial.pop();
+        }
+    }
+
+    /// Adds the words below `other` to the words below this node.
+    fn merge(&mut self, other: &TrieNode) {
+        if other.is_terminal {
+            self.is_terminal = true;
+        }
+        for (c, other_child) in other.children.iter() {
+            self.children
+                .entry(*c)
+                .or_insert_with(|| TrieNode {
+                    contents: Some(*c),
+                    children: FxHashMap::default(),
+                    is_terminal: false,
+                })
+                .merge(other_child);
+        }
+    }
+
+    /// Collects the words below this node of `min_len` to `max_len` letters in total,
+    /// starting with the letters of `fixed`, where a space matches any letter.
+    fn words_matching(
+        &self,
+        fixed: &[char],
+        min_len: usize,
+        max_len: usize,
+        partial: &mut String,
+        result: &mut Vec<String>,
+    ) {
+        let len = partial.chars().count();
+        if self.is_terminal && len >= min_len && len <= max_len {
+            result.push(partial.clone());
+        }
+        if len >= max_len {
+            return;
+        }
+        for (c, child) in self.children.iter() {
+            if fixed.get(len).is_none_or(|f| *f == ' ' || f == c) {
+                partial.push(*c);
+                child.words_matching(fixed, min_len, max_len, partial, result);
+                partial.pop();
+            }
+        }
+    }
+
+    /// Collects the words below this node using exactly the letters of `available`, with
+    /// `remaining` letters left in it.
+    fn anagrams(
+        &self,
+        available: &mut FxHashMap<char, usize>,
+        remaining: usize,
+        partial: &mut String,
+        result: &mut Vec<String>,
+    ) {
+        if remaining == 0 {
+            if self.is_terminal {
+                result.push(partial.clone());
+            }
+            return;
+        }
+        for (c, child) in self.children.iter() {
+            match available.get_mut(c) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => continue,
+            }
+            partial.push(*c);
+            child.anagrams(available, remaining - 1, partial, result);
+            partial.pop();
+            *available.get_mut(c).unwrap() += 1;
+        }
+    }
+
     pub fn is_viable<T: Iterator<Item = char> + Clone>(&self, mut chars: T) -> bool {
         match chars.next() {
             None => self.is_terminal,
@@ -132,6 +368,170 @@ impl TrieNode {
     }
 }
 
+/// Counts describing the shape of a `Trie`, see `Trie::stats`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TrieStats {
+    /// The number of nodes, including the root.
+    pub node_count: usize,
+    pub word_count: usize,
+    /// The length of the longest word.
+    pub max_depth: usize,
+}
+
+/// How long a `Trie` took to load and roughly how much memory it uses,
+/// see `Trie::load_with_report`.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadReport {
+    pub duration: Duration,
+    pub node_count: usize,
+    /// The node count times the size of a node. Heap allocations of the children
+    /// maps and original word forms are not counted.
+    pub estimated_bytes: usize,
+}
+
+/// The patterns `Trie::lint` flags as likely abbreviations or partial words.
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    /// Words of at most this many letters without any of `vowels` are flagged.
+    pub max_vowelless_length: usize,
+    pub vowels: String,
+    /// Known prefixes and suffixes, flagged when listed as words on their own. Words
+    /// starting or ending with a hyphen are always flagged.
+    pub affixes: Vec<String>,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        LintOptions {
+            max_vowelless_length: 4,
+            vowels: String::from("AEIOUYĂÂÎ"),
+            affixes: [
+                "ANTI", "DIS", "INTER", "MIS", "NON", "PRE", "SUB", "TRANS", "UN", "ING", "ISM",
+                "NESS", "MENT", "TION",
+            ]
+            .iter()
+            .map(|affix| affix.to_string())
+            .collect(),
+        }
+    }
+}
+
+/// Why `Trie::lint` flagged a word.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LintReason {
+    Vowelless,
+    Affix,
+}
+
+/// The words flagged by `Trie::lint`, in the order of the word list.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct LintReport {
+    pub flagged: Vec<(String, LintReason)>,
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (word, reason) in self.flagged.iter() {
+            let reason = match reason {
+                LintReason::Vowelless => "short word without vowels",
+                LintReason::Affix => "prefix or suffix",
+            };
+            writeln!(f, "{}\t{}", word, reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Several named tries queried together, e.g. to search multiple dictionaries at once
+/// and tell which of them each match comes from.
+#[derive(Default)]
+pub struct MultiTrie {
+    tries: Vec<(String, Trie)>,
+}
+
+impl MultiTrie {
+    pub fn new() -> MultiTrie {
+        MultiTrie::default()
+    }
+
+    /// Adds a trie under `name`. Sources are reported in the order the tries were added.
+    pub fn add(&mut self, name: &str, trie: Trie) -> &mut Self {
+        self.tries.push((name.to_string(), trie));
+        self
+    }
+
+    /// Returns the words matching `pattern` in any of the tries, sorted, each with the
+    /// names of the tries it appears in.
+    pub fn words<T: Iterator<Item = char> + Clone>(
+        &self,
+        pattern: T,
+    ) -> Vec<(String, Vec<&str>)> {
+        let mut result: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for (name, trie) in &self.tries {
+            for word in trie.words(pattern.clone()) {
+                result.entry(word).or_default().push(name);
+            }
+        }
+        result.into_iter().collect()
+    }
+}
+
+/// An iterator over the words of a `Trie` in sorted order, created by `Trie::iter_all_words`.
+pub struct AllWords<'t> {
+    stack: Vec<(&'t TrieNode, String)>,
+}
+
+impl<'t> Iterator for AllWords<'t> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some((node, word)) = self.stack.pop() {
+            let mut children: Vec<(&char, &TrieNode)> = node.children.iter().collect();
+            // pushed in reverse so that the smallest letter is visited first
+            children.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+            for (c, child) in children {
+                let mut child_word = word.clone();
+                child_word.push(*c);
+                self.stack.push((child, child_word));
+            }
+            if node.is_terminal {
+                return Some(word);
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over the prefixes of the words of a `Trie` in sorted order, created by
+/// `Trie::iter_prefixes`.
+pub struct Prefixes<'t> {
+    stack: Vec<(&'t TrieNode, String, usize)>,
+    max_len: usize,
+}
+
+impl<'t> Iterator for Prefixes<'t> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some((node, prefix, len)) = self.stack.pop() {
+            if len < self.max_len {
+                let mut children: Vec<(&char, &TrieNode)> = node.children.iter().collect();
+                // pushed in reverse so that the smallest letter is visited first
+                children.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+                for (c, child) in children {
+                    let mut child_prefix = prefix.clone();
+                    child_prefix.push(*c);
+                    self.stack.push((child, child_prefix, len + 1));
+                }
+            }
+            if len > 0 {
+                return Some(prefix);
+            }
+        }
+        None
+    }
+}
+
 impl fmt::Display for TrieNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         self.display_helper(f, 1, true)
@@ -141,6 +541,11 @@ impl fmt::Display for TrieNode {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Trie {
     pub root: TrieNode,
+    /// The words as written in the source list, by their uppercase form, for the words of
+    /// `build_preserving_case` that differ from it.
+    originals: FxHashMap<String, String>,
+    /// The non-zero scores of the words of `build_scored`, by word.
+    scores: FxHashMap<String, i32>,
 }
 
 impl fmt::Display for Trie {
@@ -154,13 +559,48 @@ impl Trie {
         Trie::load("en")
     }
 
+    /// Loads the trie serialized in `words/<name>.bincode`, see `Trie::build_bin_code`.
+    /// Files serialized before word scores and original forms were kept hold the nodes
+    /// only, and load with no scores or original forms.
     pub fn load(name: &str) -> Result<Trie, Error> {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push(format!("words/{}.bincode", name));
         let file = File::open(path.clone())
             .map_err(|e| Error::new(e.kind(), format!("Could not open file {:?}", path)))?;
-        bincode::deserialize_from::<File, Trie>(file)
-            .map_err(|e| Error::new(InvalidInput, e.to_string()))
+        Trie::read_bin_code(io::BufReader::new(file))
+    }
+
+    /// Reads a serialized trie: its nodes, then its original forms and scores unless the
+    /// input ends after the nodes.
+    fn read_bin_code<R: BufRead>(mut reader: R) -> Result<Trie, Error> {
+        let invalid = |e: bincode::Error| Error::new(InvalidInput, e.to_string());
+        let root: TrieNode = bincode::deserialize_from(&mut reader).map_err(invalid)?;
+        let (originals, scores) = if reader.fill_buf()?.is_empty() {
+            (FxHashMap::default(), FxHashMap::default())
+        } else {
+            bincode::deserialize_from(&mut reader).map_err(invalid)?
+        };
+        Ok(Trie {
+            root,
+            originals,
+            scores,
+        })
+    }
+
+    /// Loads a trie like `Trie::load`, also reporting how long loading took and an
+    /// estimate of the memory used by the trie, to help choose word lists for deployment.
+    pub fn load_with_report(name: &str) -> Result<(Trie, LoadReport), Error> {
+        let start_time = Instant::now();
+        let trie = Trie::load(name)?;
+        let duration = start_time.elapsed();
+        let node_count = trie.stats().node_count;
+        let report = LoadReport {
+            duration,
+            node_count,
+            // every node but the root is stored in its parent's children keyed by a char
+            estimated_bytes: node_count * (mem::size_of::<TrieNode>() + mem::size_of::<char>()),
+        };
+        Ok((trie, report))
     }
 
     pub fn build(words: Vec<String>) -> Trie {
@@ -171,20 +611,436 @@ impl Trie {
         };
 
         for word in words.iter() {
-            root = root.add_sequence(&word);
+            root = root.add_sequence(word);
         }
 
-        Trie { root }
+        Trie {
+            root,
+            originals: FxHashMap::default(),
+            scores: FxHashMap::default(),
+        }
     }
 
-    pub fn build_bin_code(file_path: &PathBuf) -> Result<PathBuf, Error> {
-        let name = file_path.display().to_string();
-        let file_name = file_path
+    /// Builds a trie from words in any casing. Words are stored uppercase, like
+    /// `build_bin_code` does, but the original form of each word that differs from
+    /// its uppercase form is kept and can be retrieved with `original_form`.
+    pub fn build_preserving_case(words: Vec<String>) -> Trie {
+        let mut root = TrieNode {
+            contents: None,
+            children: FxHashMap::default(),
+            is_terminal: false,
+        };
+
+        let mut originals = FxHashMap::default();
+
+        for word in words.iter() {
+            let uppercase = word.to_uppercase();
+            root = root.add_sequence(&uppercase);
+            if uppercase != *word {
+                originals.insert(uppercase, word.clone());
+            }
+        }
+
+        Trie {
+            root,
+            originals,
+            scores: FxHashMap::default(),
+        }
+    }
+
+    /// Builds a trie from words with a score each, e.g. their frequency. Scores can be
+    /// retrieved with `score`.
+    pub fn build_scored(words: Vec<(String, i32)>) -> Trie {
+        let mut root = TrieNode {
+            contents: None,
+            children: FxHashMap::default(),
+            is_terminal: false,
+        };
+
+        let mut scores: FxHashMap<String, i32> = FxHashMap::default();
+
+        for (word, score) in words.iter() {
+            root = root.add_sequence(word);
+            // a word listed more than once keeps its highest score
+            scores
+                .entry(word.clone())
+                .and_modify(|best| *best = (*best).max(*score))
+                .or_insert(*score);
+        }
+        // words without a score count as 0, so only the others are kept
+        scores.retain(|_, score| *score != 0);
+
+        Trie {
+            root,
+            originals: FxHashMap::default(),
+            scores,
+        }
+    }
+
+    /// Builds a trie from a word list with one word per line, like the `.txt` files of
+    /// `build_bin_code`, inserting the words as they are read so that huge lists are never
+    /// held in memory at once. Words are stored uppercase, and empty lines and lines starting
+    /// with `#` are skipped.
+    pub fn build_from_reader<R: BufRead>(reader: R) -> Result<Trie, Error> {
+        let mut trie = Trie::build(vec![]);
+        for line in reader.lines() {
+            let line = line?;
+            let word = line.trim();
+            if !word.is_empty() && !word.starts_with('#') {
+                trie.insert(word);
+            }
+        }
+        Ok(trie)
+    }
+
+    /// Adds a word to the trie in place, in uppercase like the word lists, e.g. to add theme
+    /// words to a loaded dictionary. A word already in the trie keeps its score.
+    pub fn insert(&mut self, word: &str) {
+        let mut node = &mut self.root;
+        for c in word.to_uppercase().chars() {
+            node = node.children.entry(c).or_insert_with(|| TrieNode {
+                contents: Some(c),
+                children: FxHashMap::default(),
+                is_terminal: false,
+            });
+        }
+        node.is_terminal = true;
+    }
+
+    /// Adds every word of `other` to the trie, e.g. a theme list to a base dictionary, so
+    /// that a single filler uses both. A word scored in both keeps its highest score.
+    pub fn merge(&mut self, other: &Trie) {
+        self.root.merge(&other.root);
+        for (word, score) in other.scores.iter() {
+            self.scores
+                .entry(word.clone())
+                .and_modify(|best| *best = (*best).max(*score))
+                .or_insert(*score);
+        }
+        for (word, original) in other.originals.iter() {
+            self.originals
+                .entry(word.clone())
+                .or_insert_with(|| original.clone());
+        }
+    }
+
+    /// Removes a word from the trie, in any casing, e.g. a bad entry of a loaded dictionary.
+    /// Words it is a prefix of are kept. Returns whether the word was in the trie.
+    pub fn remove(&mut self, word: &str) -> bool {
+        let uppercase = word.to_uppercase();
+        let chars: Vec<char> = uppercase.chars().collect();
+        self.originals.remove(&uppercase);
+        self.scores.remove(&uppercase);
+        self.root.remove(&chars)
+    }
+
+    /// Builds a trie from a word list file and serializes it to `words/<file stem>.bincode`.
+    ///
+    /// Supported formats are a JSON array of words (`.json`), one word per line (`.txt`) and
+    /// one `word<TAB>frequency` pair per line (`.tsv`), where the frequency becomes the score
+    /// of the word. In text formats empty lines and lines starting with `#` are skipped, and
+    /// a line that can't be read, e.g. because it isn't UTF-8, fails the build.
+    pub fn build_bin_code(file_path: &Path) -> Result<PathBuf, Error> {
+        let words = Trie::load_word_file(file_path)?;
+        Trie::write_bin_code(file_path, words)
+    }
+
+    /// Builds a trie like `build_bin_code`, and also reports the words matching the
+    /// suspicious patterns of `options` so the list can be reviewed. Flagged words are kept
+    /// in the trie. The report is written to `words/<file stem>.lint.txt`, one
+    /// `word<TAB>reason` line per flagged word.
+    pub fn build_bin_code_with_lint(
+        file_path: &Path,
+        options: &LintOptions,
+    ) -> Result<(PathBuf, LintReport), Error> {
+        let words = Trie::load_word_file(file_path)?;
+        let report = Trie::lint(words.iter().map(|(word, _)| word.as_str()), options);
+        let report_path = PathBuf::from(format!("words/{}.lint.txt", Trie::file_stem(file_path)?));
+        fs::write(report_path, report.to_string())?;
+        Ok((Trie::write_bin_code(file_path, words)?, report))
+    }
+
+    /// Returns the words that look like abbreviations or partial words according to
+    /// `options`, ignoring case.
+    pub fn lint<'w, I: IntoIterator<Item = &'w str>>(
+        words: I,
+        options: &LintOptions,
+    ) -> LintReport {
+        let vowels = options.vowels.to_uppercase();
+        let affixes: HashSet<String> = options.affixes.iter().map(|a| a.to_uppercase()).collect();
+        let flagged = words
+            .into_iter()
+            .filter_map(|word| {
+                let uppercase = word.to_uppercase();
+                let reason = if uppercase.starts_with('-')
+                    || uppercase.ends_with('-')
+                    || affixes.contains(&uppercase)
+                {
+                    LintReason::Affix
+                } else if uppercase.chars().count() <= options.max_vowelless_length
+                    && !uppercase.chars().any(|c| vowels.contains(c))
+                {
+                    LintReason::Vowelless
+                } else {
+                    return None;
+                };
+                Some((word.to_string(), reason))
+            })
+            .collect();
+        LintReport { flagged }
+    }
+
+    pub fn words<T: Iterator<Item = char> + Clone>(&self, pattern: T) -> Vec<String> {
+        let mut result = Vec::with_capacity(4);
+        let mut partial = String::with_capacity(4);
+        self.root.words(pattern, &mut partial, &mut result);
+        result
+    }
+
+    /// Returns the words matching `pattern` like `Trie::words`, in the same order, but only
+    /// those whose letter in each open square of the pattern is among the `letters` of that
+    /// square, when given. Open squares past the end of `letters` accept any letter.
+    pub fn words_with_letters<T: Iterator<Item = char> + Clone>(
+        &self,
+        pattern: T,
+        letters: &[Option<FxHashSet<char>>],
+    ) -> Vec<String> {
+        let mut result = vec![];
+        let mut partial = String::with_capacity(4);
+        self.root
+            .words_with_letters(pattern, letters, &mut partial, &mut result);
+        result
+    }
+
+    /// Returns the words matching `pattern` with `min_len` to `max_len` letters, in sorted
+    /// order. Unlike `Trie::words`, the pattern can end with `*` for any remaining letters,
+    /// e.g. `AB*` for the words starting with AB, and spaces stand for any letter. A
+    /// pattern without `*` only matches words of its own length. The lookup is
+    /// case-insensitive.
+    pub fn words_matching(&self, pattern: &str, min_len: usize, max_len: usize) -> Vec<String> {
+        let pattern = pattern.to_uppercase();
+        let (fixed, any_suffix) = match pattern.strip_suffix('*') {
+            Some(fixed) => (fixed, true),
+            None => (pattern.as_str(), false),
+        };
+        let fixed: Vec<char> = fixed.chars().collect();
+        let min_len = min_len.max(fixed.len());
+        let max_len = if any_suffix {
+            max_len
+        } else {
+            max_len.min(fixed.len())
+        };
+        let mut result = vec![];
+        let mut partial = String::new();
+        self.root
+            .words_matching(&fixed, min_len, max_len, &mut partial, &mut result);
+        result.sort_unstable();
+        result
+    }
+
+    /// Counts the words matching `pattern` like `Trie::words`, up to `limit`, without
+    /// collecting them.
+    pub fn count_words<T: Iterator<Item = char> + Clone>(&self, pattern: T, limit: usize) -> usize {
+        self.root.count_words(pattern, limit)
+    }
+
+    /// Like `words`, with the score of each word, 0 for unscored word lists.
+    pub fn words_scored<T: Iterator<Item = char> + Clone>(&self, pattern: T) -> Vec<(String, i32)> {
+        self.words(pattern)
+            .into_iter()
+            .map(|word| {
+                let score = self.scores.get(&word).copied().unwrap_or(0);
+                (word, score)
+            })
+            .collect()
+    }
+
+    pub fn is_viable<T: Iterator<Item = char> + Clone>(&self, chars: T) -> bool {
+        self.root.is_viable(chars)
+    }
+
+    /// Returns the score of a word, or `None` if the word is not in the trie.
+    /// The lookup is case-insensitive.
+    pub fn score(&self, word: &str) -> Option<i32> {
+        let uppercase = word.to_uppercase();
+        self.root
+            .find(&uppercase)
+            .filter(|node| node.is_terminal)
+            .map(|_| self.scores.get(&uppercase).copied().unwrap_or(0))
+    }
+
+    /// Returns the letters that can follow `prefix` in some word of the trie, or an empty
+    /// set if no word starts with `prefix`. The lookup is case-insensitive.
+    pub fn next_letters(&self, prefix: &str) -> HashSet<char> {
+        self.root
+            .find(&prefix.to_uppercase())
+            .map(|node| node.children.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns up to `limit` words of any length starting with `prefix`, in sorted order,
+    /// e.g. to suggest answers as they are typed. The prefix itself is included if it is a
+    /// word. The lookup is case-insensitive.
+    pub fn with_prefix(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let prefix = prefix.to_uppercase();
+        match self.root.find(&prefix) {
+            Some(node) => AllWords {
+                stack: vec![(node, prefix)],
+            }
+            .take(limit)
+            .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Whether `word` is in the trie. The lookup is case-insensitive.
+    pub fn contains(&self, word: &str) -> bool {
+        self.root
+            .find(&word.to_uppercase())
+            .is_some_and(|node| node.is_terminal)
+    }
+
+    /// Returns the words of the trie within `max_distance` insertions, deletions or
+    /// substitutions of `word`, closest first and then in alphabetical order. The word
+    /// itself is included if it is in the trie. The lookup is case-insensitive.
+    pub fn fuzzy(&self, word: &str, max_distance: usize) -> Vec<String> {
+        let target: Vec<char> = word.to_uppercase().chars().collect();
+        let first_row: Vec<usize> = (0..=target.len()).collect();
+        let mut result = vec![];
+        let mut partial = String::new();
+        if self.root.is_terminal && target.len() <= max_distance {
+            result.push((target.len(), String::new()));
+        }
+        let root = &self.root;
+        root.fuzzy(&target, &first_row, max_distance, &mut partial, &mut result);
+        result.sort_unstable();
+        result.into_iter().map(|(_, word)| word).collect()
+    }
+
+    /// Returns the words of the trie that use exactly the letters of `letters`, each as
+    /// many times as it appears, in sorted order. The letters themselves are included if
+    /// they spell a word. The lookup is case-insensitive.
+    pub fn anagrams(&self, letters: &str) -> Vec<String> {
+        let mut available: FxHashMap<char, usize> = FxHashMap::default();
+        let mut remaining = 0;
+        for c in letters.to_uppercase().chars() {
+            *available.entry(c).or_insert(0) += 1;
+            remaining += 1;
+        }
+        let mut result = vec![];
+        let mut partial = String::new();
+        self.root
+            .anagrams(&mut available, remaining, &mut partial, &mut result);
+        result.sort_unstable();
+        result
+    }
+
+    /// Checks the spelling of the words of `text`, such as a clue, against the trie.
+    ///
+    /// The text is split into words at every character that is not a letter. Returns each
+    /// word not in the trie, as written in the text and in order, with the words of the trie
+    /// within `SPELLCHECK_MAX_DISTANCE` edits of it as suggestions, closest first.
+    pub fn spellcheck(&self, text: &str) -> Vec<(String, Vec<String>)> {
+        text.split(|c: char| !c.is_alphabetic())
+            .filter(|token| !token.is_empty() && !self.contains(token))
+            .map(|token| {
+                let suggestions = self.fuzzy(token, SPELLCHECK_MAX_DISTANCE);
+                (token.to_string(), suggestions)
+            })
+            .collect()
+    }
+
+    /// Whether the trie holds no words, as when built from an empty list. The empty word
+    /// doesn't count.
+    pub fn is_empty(&self) -> bool {
+        self.root.children.is_empty()
+    }
+
+    /// Counts the nodes and words of the trie.
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = TrieStats {
+            node_count: 0,
+            word_count: 0,
+            max_depth: 0,
+        };
+        let mut stack = vec![(&self.root, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            stats.node_count += 1;
+            if node.is_terminal {
+                stats.word_count += 1;
+            }
+            stats.max_depth = stats.max_depth.max(depth);
+            stack.extend(node.children.values().map(|child| (child, depth + 1)));
+        }
+        stats
+    }
+
+    /// Returns every word of the trie in sorted order. See `iter_all_words` to avoid
+    /// collecting large word lists.
+    pub fn all_words(&self) -> Vec<String> {
+        self.iter_all_words().collect()
+    }
+
+    /// Iterates over every word of the trie in sorted order.
+    pub fn iter_all_words(&self) -> AllWords<'_> {
+        AllWords {
+            stack: vec![(&self.root, String::new())],
+        }
+    }
+
+    /// Returns every prefix of one to `max_len` letters of the words of the trie, in sorted
+    /// order. Words of at most `max_len` letters are prefixes of themselves. There can be
+    /// many prefixes for large tries, see `iter_prefixes` to avoid collecting them.
+    pub fn prefixes(&self, max_len: usize) -> Vec<String> {
+        self.iter_prefixes(max_len).collect()
+    }
+
+    /// Iterates over the prefixes of `Trie::prefixes` in sorted order.
+    pub fn iter_prefixes(&self, max_len: usize) -> Prefixes<'_> {
+        Prefixes {
+            stack: vec![(&self.root, String::new(), 0)],
+            max_len,
+        }
+    }
+
+    /// Compares two tries and returns the words only present in `self` and the words
+    /// only present in `other`, both sorted.
+    pub fn diff(&self, other: &Trie) -> (Vec<String>, Vec<String>) {
+        let mut only_self = vec![];
+        let mut only_other = vec![];
+        let mut partial = String::new();
+        TrieNode::diff(
+            Some(&self.root),
+            Some(&other.root),
+            &mut partial,
+            &mut only_self,
+            &mut only_other,
+        );
+        (only_self, only_other)
+    }
+
+    /// Returns the word as it was written in the source list, or `None` if the word
+    /// is not in the trie. The lookup is case-insensitive. Words without a stored
+    /// original form are returned as stored in the trie.
+    pub fn original_form(&self, word: &str) -> Option<String> {
+        let uppercase = word.to_uppercase();
+        self.root
+            .find(&uppercase)
+            .filter(|node| node.is_terminal)
+            .map(|_| self.originals.get(&uppercase).cloned().unwrap_or(uppercase))
+    }
+
+    fn file_stem(file_path: &Path) -> Result<&str, Error> {
+        file_path
             .file_stem()
             .ok_or_else(|| Error::new(InvalidInput, "File has no stem"))?
             .to_str()
-            .ok_or_else(|| Error::new(InvalidInput, "File stem is not valid"))?;
-        let out_path = PathBuf::from(format!("words/{}.bincode", file_name));
+            .ok_or_else(|| Error::new(InvalidInput, "File stem is not valid"))
+    }
+
+    /// Reads the words of a word list file in one of the formats of `build_bin_code`.
+    fn load_word_file(file_path: &Path) -> Result<Vec<(String, i32)>, Error> {
+        let name = file_path.display().to_string();
         let file = File::open(file_path)
             .map_err(|e| Error::new(e.kind(), format!("Could not open file {}", name)))?;
         let extension = file_path
@@ -192,48 +1048,74 @@ impl Trie {
             .and_then(|e| e.to_str())
             .ok_or_else(|| Error::new(InvalidInput, "File has no extension"))?;
         let words = match extension {
-            "json" => Trie::load_words_from_json(&file),
-            "txt" => Trie::load_words_from_text(&file),
+            "json" => Trie::unscored(Trie::load_words_from_json(&file)),
+            "txt" => Trie::unscored(Trie::load_words_from_text(&file)?),
+            "tsv" => Trie::load_words_from_tsv(&file)?,
             ext => Err(Error::new(
                 InvalidInput,
                 format!("Unsupported file format: {}", ext),
             ))?,
         };
+        Ok(words)
+    }
+
+    fn write_bin_code(file_path: &Path, words: Vec<(String, i32)>) -> Result<PathBuf, Error> {
+        let out_path = PathBuf::from(format!("words/{}.bincode", Trie::file_stem(file_path)?));
         let words = Trie::make_words_uppercase(words);
-        let trie = Trie::build(words);
+        let trie = Trie::build_scored(words);
         let trie_file = File::create(&out_path)?;
         bincode::serialize_into(trie_file, &trie)
             .map_err(|e| Error::new(InvalidInput, e.to_string()))?;
         Ok(out_path)
     }
 
-    pub fn words<T: Iterator<Item = char> + Clone>(&self, pattern: T) -> Vec<String> {
-        let mut result = Vec::with_capacity(4);
-        let mut partial = String::with_capacity(4);
-        self.root.words(pattern, &mut partial, &mut result);
-        result
+    fn load_words_from_json(file: &File) -> Vec<String> {
+        serde_json::from_reader(file).expect("JSON was not well-formatted")
     }
 
-    pub fn is_viable<T: Iterator<Item = char> + Clone>(&self, chars: T) -> bool {
-        self.root.is_viable(chars)
+    fn load_words_from_text<R: io::Read>(reader: R) -> Result<Vec<String>, Error> {
+        let mut words = vec![];
+        for line in io::BufReader::new(reader).lines() {
+            let line = line?;
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                words.push(line.to_string());
+            }
+        }
+        Ok(words)
     }
 
-    fn load_words_from_json(file: &File) -> Vec<String> {
-        let words = serde_json::from_reader(file).expect("JSON was not well-formatted");
-        words
+    /// Reads `word<TAB>frequency` lines. Frequencies larger than `i32::MAX` are capped.
+    fn load_words_from_tsv<R: io::Read>(reader: R) -> Result<Vec<(String, i32)>, Error> {
+        let mut words = vec![];
+        for line in io::BufReader::new(reader).lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split('\t');
+            let word = fields.next().unwrap_or_default().trim();
+            let frequency = fields
+                .next()
+                .and_then(|f| f.trim().parse::<u64>().ok())
+                .ok_or_else(|| {
+                    Error::new(InvalidInput, format!("Invalid frequency in line: {}", line))
+                })?;
+            words.push((word.to_string(), frequency.min(i32::MAX as u64) as i32));
+        }
+        Ok(words)
     }
 
-    fn load_words_from_text(file: &File) -> Vec<String> {
-        let words = io::BufReader::new(file)
-            .lines()
-            .flatten()
-            .filter(|s| !s.is_empty() && !s.starts_with("#"))
-            .collect::<Vec<String>>();
-        words
+    fn unscored(words: Vec<String>) -> Vec<(String, i32)> {
+        words.into_iter().map(|word| (word, 0)).collect()
     }
 
-    fn make_words_uppercase(words: Vec<String>) -> Vec<String> {
-        words.into_iter().map(|s| s.to_uppercase()).collect()
+    fn make_words_uppercase(words: Vec<(String, i32)>) -> Vec<(String, i32)> {
+        words
+            .into_iter()
+            .map(|(word, score)| (word.to_uppercase(), score))
+            .collect()
     }
 }
 
@@ -241,8 +1123,9 @@ impl Trie {
 mod tests {
     use rustc_hash::FxHashMap;
 
-    use super::{Trie, TrieNode};
+    use super::{LintOptions, LintReason, MultiTrie, Trie, TrieNode, TrieStats};
     use std::collections::HashSet;
+    use std::io;
     use std::path::PathBuf;
 
     #[test]
@@ -258,6 +1141,23 @@ mod tests {
         assert!(trie.is_ok());
     }
 
+    #[test]
+    fn read_bin_code_works() {
+        let trie = Trie::build_scored(vec![(String::from("CAT"), 3), (String::from("DOG"), 0)]);
+        let bytes = bincode::serialize(&trie).unwrap();
+        let read = Trie::read_bin_code(bytes.as_slice()).unwrap();
+        assert_eq!(Some(3), read.score("CAT"));
+        assert!(read.is_viable("DOG".chars()));
+
+        // files serialized before scores and original forms were kept end after the nodes
+        let bytes = bincode::serialize(&trie.root).unwrap();
+        let read = Trie::read_bin_code(bytes.as_slice()).unwrap();
+        assert_eq!(Some(0), read.score("CAT"));
+        assert!(read.is_viable("DOG".chars()));
+
+        assert!(Trie::read_bin_code(&bytes[..bytes.len() - 1]).is_err());
+    }
+
     #[test]
     #[ignore]
     fn rebuild_serialized_trie_ro_dex() {
@@ -285,6 +1185,10 @@ mod tests {
         if let Err(e) = result {
             panic!("{}", e);
         }
+        let result = Trie::build_bin_code(&PathBuf::from("words/ro_dex_095.txt"));
+        if let Err(e) = result {
+            panic!("{}", e);
+        }
     }
 
     #[test]
@@ -373,7 +1277,7 @@ mod tests {
             String::from("be"),
         ]);
 
-        let expected: HashSet<String> = vec![String::from("bass"), String::from("bess")]
+        let expected: HashSet<String> = [String::from("bass"), String::from("bess")]
             .iter()
             .cloned()
             .collect();
@@ -382,4 +1286,445 @@ mod tests {
         let actual: HashSet<String> = trie.words(iter.chars()).iter().cloned().collect();
         assert_eq!(expected, actual,)
     }
+
+    #[test]
+    fn insert_works() {
+        let mut trie = Trie::build(vec![String::from("BASS"), String::from("BESS")]);
+        assert!(!trie.is_viable("BOSS".chars()));
+
+        trie.insert("boss");
+        trie.insert("BASS");
+
+        assert!(trie.is_viable("BOSS".chars()));
+        assert!(!trie.is_viable("boss".chars()));
+        let expected: HashSet<String> = ["BASS", "BESS", "BOSS"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let actual: HashSet<String> = trie.words("B SS".chars()).into_iter().collect();
+        assert_eq!(expected, actual);
+        assert_eq!(3, trie.stats().word_count);
+    }
+
+    #[test]
+    fn build_from_reader_works() {
+        let text = "# comment\nbass\n\n  Bess \nBASS\n";
+
+        let trie = Trie::build_from_reader(io::Cursor::new(text)).unwrap();
+
+        let expected: HashSet<String> = ["BASS", "BESS"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let actual: HashSet<String> = trie.words("B SS".chars()).into_iter().collect();
+        assert_eq!(expected, actual);
+        assert_eq!(2, trie.stats().word_count);
+        assert!(!trie.is_viable("#".chars()));
+
+        let invalid = io::Cursor::new(vec![b'A', 0xff, b'\n']);
+        assert!(Trie::build_from_reader(invalid).is_err());
+    }
+
+    #[test]
+    fn remove_works() {
+        let mut trie = Trie::build(vec![String::from("BASE")]);
+        trie.insert("bass");
+        trie.insert("bassoon");
+
+        assert!(trie.remove("bass"));
+        assert!(!trie.remove("bass"));
+        assert!(!trie.remove("bas"));
+
+        assert_eq!(vec![String::from("BASSOON")], trie.words("BASSOON".chars()));
+        assert!(trie.words("BASS".chars()).is_empty());
+        assert!(trie.is_viable("BASSOON".chars()));
+        assert!(trie.is_viable("BASE".chars()));
+
+        let node_count = trie.stats().node_count;
+        assert!(trie.remove("BASSOON"));
+        assert_eq!(node_count - 4, trie.stats().node_count);
+        assert_eq!(1, trie.stats().word_count);
+    }
+
+    #[test]
+    fn with_prefix_works() {
+        let trie = Trie::build(vec![
+            String::from("BASS"),
+            String::from("BASSOON"),
+            String::from("BASSET"),
+            String::from("BAT"),
+            String::from("BEST"),
+        ]);
+
+        assert_eq!(
+            vec![
+                String::from("BASS"),
+                String::from("BASSET"),
+                String::from("BASSOON")
+            ],
+            trie.with_prefix("bass", 10)
+        );
+        assert_eq!(
+            vec![String::from("BASS"), String::from("BASSET")],
+            trie.with_prefix("BA", 2)
+        );
+        assert_eq!(5, trie.with_prefix("", 10).len());
+        assert!(trie.with_prefix("BASSO", 0).is_empty());
+        assert!(trie.with_prefix("BO", 10).is_empty());
+    }
+
+    #[test]
+    fn words_matching_works() {
+        let trie = Trie::build(vec![
+            String::from("AB"),
+            String::from("ABBA"),
+            String::from("ABBOT"),
+            String::from("ABLE"),
+            String::from("ACE"),
+            String::from("BABBLE"),
+        ]);
+
+        assert_eq!(
+            vec!["AB", "ABBA", "ABBOT", "ABLE"],
+            trie.words_matching("ab*", 0, 10)
+        );
+        assert_eq!(vec!["ABBA", "ABLE"], trie.words_matching("AB*", 3, 4));
+        assert_eq!(vec!["ABBA", "ABBOT"], trie.words_matching("A B*", 0, 10));
+        assert_eq!(6, trie.words_matching("*", 0, 10).len());
+        assert_eq!(vec!["ABBA", "ABLE"], trie.words_matching("AB  ", 0, 10));
+        assert!(trie.words_matching("AB  ", 0, 3).is_empty());
+        let mut words = trie.words("AB  ".chars());
+        words.sort_unstable();
+        assert_eq!(words, trie.words_matching("AB  ", 0, 10));
+    }
+
+    #[test]
+    fn merge_works() {
+        let mut trie =
+            Trie::build_scored(vec![(String::from("BASS"), 1), (String::from("CAT"), 5)]);
+        let theme = Trie::build_scored(vec![
+            (String::from("BASSOON"), 2),
+            (String::from("BAS"), 3),
+            (String::from("CAT"), 4),
+        ]);
+
+        trie.merge(&theme);
+
+        for word in ["BAS", "BASS", "BASSOON", "CAT"].iter() {
+            assert!(trie.is_viable(word.chars()));
+        }
+        assert_eq!(4, trie.stats().word_count);
+        assert_eq!(Some(5), trie.score("CAT"));
+        assert_eq!(Some(2), trie.score("BASSOON"));
+        assert_eq!(Some(1), trie.score("BASS"));
+        assert_eq!(Some(0), Trie::build(vec![String::from("CAT")]).score("CAT"));
+        assert!(theme.words("BASS".chars()).is_empty());
+    }
+
+    #[test]
+    fn lint_works() {
+        let words = ["cat", "NTH", "pre", "-ness", "rhythm", "sky", "TV"];
+
+        let report = Trie::lint(words.iter().copied(), &LintOptions::default());
+
+        assert_eq!(
+            vec![
+                (String::from("NTH"), LintReason::Vowelless),
+                (String::from("pre"), LintReason::Affix),
+                (String::from("-ness"), LintReason::Affix),
+                (String::from("TV"), LintReason::Vowelless),
+            ],
+            report.flagged
+        );
+        assert!(report
+            .to_string()
+            .starts_with("NTH\tshort word without vowels\n"));
+    }
+
+    #[test]
+    fn load_words_from_text_trims_whitespace() {
+        let text = "# comment\nBASS \n\tBESS\t\n   \nBE\r\n";
+
+        let words = Trie::load_words_from_text(text.as_bytes()).unwrap();
+
+        assert_eq!(
+            vec![String::from("BASS"), String::from("BESS"), String::from("BE")],
+            words
+        );
+        let trie = Trie::build(words);
+        assert!(trie.is_viable("BASS".chars()));
+        assert_eq!(0, trie.words("BASS ".chars()).len());
+
+        let invalid: &[u8] = b"BASS\n\xff\nBESS\n";
+        assert!(Trie::load_words_from_text(invalid).is_err());
+    }
+
+    #[test]
+    fn load_words_from_tsv_works() {
+        let tsv = "# word\tfrequency\nthe\t23135851162\nbass\t1200\n\nBASS\t300\nbess\t7\n";
+
+        let words = Trie::make_words_uppercase(Trie::load_words_from_tsv(tsv.as_bytes()).unwrap());
+        let trie = Trie::build_scored(words);
+
+        assert_eq!(Some(i32::MAX), trie.score("THE"));
+        assert_eq!(Some(1200), trie.score("bass"));
+        assert_eq!(Some(7), trie.score("BESS"));
+        assert_eq!(None, trie.score("BES"));
+        assert!(trie.is_viable("B SS".chars()));
+
+        assert!(Trie::load_words_from_tsv("bass\n".as_bytes()).is_err());
+        assert!(Trie::load_words_from_tsv("bass\tmany\n".as_bytes()).is_err());
+        let invalid: &[u8] = b"bass\t1200\n\xff\t7\n";
+        assert!(Trie::load_words_from_tsv(invalid).is_err());
+    }
+
+    #[test]
+    fn next_letters_works() {
+        let trie = Trie::build(vec![
+            String::from("BASS"),
+            String::from("BATS"),
+            String::from("BASE"),
+            String::from("BE"),
+        ]);
+
+        let expected: HashSet<char> = ['S', 'T'].iter().copied().collect();
+        assert_eq!(expected, trie.next_letters("BA"));
+        assert_eq!(expected, trie.next_letters("ba"));
+        let expected: HashSet<char> = ['A', 'E'].iter().copied().collect();
+        assert_eq!(expected, trie.next_letters("B"));
+        assert!(trie.next_letters("BE").is_empty());
+        assert!(trie.next_letters("Q").is_empty());
+    }
+
+    #[test]
+    fn count_words_works() {
+        let trie = Trie::build(vec![
+            String::from("BASS"),
+            String::from("BESS"),
+            String::from("BEST"),
+            String::from("BE"),
+        ]);
+
+        assert_eq!(3, trie.count_words("B   ".chars(), 10));
+        assert_eq!(2, trie.count_words("B   ".chars(), 2));
+        assert_eq!(1, trie.count_words("  ".chars(), 10));
+        assert_eq!(0, trie.count_words("C   ".chars(), 10));
+    }
+
+    #[test]
+    fn words_scored_works() {
+        let trie = Trie::build_scored(vec![
+            (String::from("CAT"), 10),
+            (String::from("COT"), 25),
+            (String::from("DOG"), 5),
+        ]);
+
+        let mut words = trie.words_scored("C T".chars());
+        words.sort_unstable();
+        assert_eq!(
+            vec![(String::from("CAT"), 10), (String::from("COT"), 25)],
+            words
+        );
+        let trie = Trie::build(vec![String::from("CAT")]);
+        assert_eq!(
+            vec![(String::from("CAT"), 0)],
+            trie.words_scored("   ".chars())
+        );
+    }
+
+    #[test]
+    fn fuzzy_works() {
+        let trie = Trie::build(vec![
+            String::from("CAT"),
+            String::from("CART"),
+            String::from("COAT"),
+            String::from("DOG"),
+        ]);
+
+        assert!(trie.contains("cat"));
+        assert!(!trie.contains("CA"));
+        assert_eq!(vec!["CAT", "CART", "COAT"], trie.fuzzy("cat", 1));
+        assert_eq!(vec!["CART", "CAT"], trie.fuzzy("CRT", 1));
+        assert_eq!(vec!["CART", "CAT", "COAT"], trie.fuzzy("CRT", 2));
+        assert_eq!(vec!["DOG"], trie.fuzzy("DIG", 1));
+        assert!(trie.fuzzy("BIRD", 2).is_empty());
+    }
+
+    #[test]
+    fn anagrams_works() {
+        let trie = Trie::build(vec![
+            String::from("LISTEN"),
+            String::from("SILENT"),
+            String::from("ENLIST"),
+            String::from("TINSEL"),
+            String::from("LISTENS"),
+            String::from("LINTEL"),
+            String::from("LIST"),
+        ]);
+
+        assert_eq!(
+            vec!["ENLIST", "LISTEN", "SILENT", "TINSEL"],
+            trie.anagrams("listen")
+        );
+        assert_eq!(vec!["LIST"], trie.anagrams("SLIT"));
+        assert!(trie.anagrams("LISTENER").is_empty());
+        assert!(trie.anagrams("").is_empty());
+    }
+
+    #[test]
+    fn spellcheck_works() {
+        let trie = Trie::build(vec![
+            String::from("A"),
+            String::from("CAT"),
+            String::from("CATS"),
+            String::from("SAT"),
+            String::from("ON"),
+            String::from("MAT"),
+            String::from("THE"),
+        ]);
+
+        assert_eq!(
+            vec![(String::from("teh"), vec![String::from("THE")])],
+            trie.spellcheck("A cat sat on teh mat.")
+        );
+        assert!(trie.spellcheck("The cat, on the mat!").is_empty());
+    }
+
+    #[test]
+    fn stats_works() {
+        let trie = Trie::build(vec![
+            String::from("BE"),
+            String::from("BEAT"),
+            String::from("CAT"),
+        ]);
+
+        assert_eq!(
+            TrieStats {
+                node_count: 8,
+                word_count: 3,
+                max_depth: 4
+            },
+            trie.stats()
+        );
+    }
+
+    #[test]
+    fn is_empty_works() {
+        assert!(Trie::build(vec![]).is_empty());
+        assert!(Trie::build(vec![String::new()]).is_empty());
+        assert!(!Trie::build(vec![String::from("A")]).is_empty());
+    }
+
+    #[test]
+    fn load_with_report_works() {
+        let (trie, report) = Trie::load_with_report("en").expect("Failed to load trie");
+
+        assert_eq!(trie.stats().node_count, report.node_count);
+        assert!(report.estimated_bytes > report.node_count);
+        assert!(Trie::load_with_report("missing").is_err());
+    }
+
+    #[test]
+    fn multi_trie_words_works() {
+        let mut multi_trie = MultiTrie::new();
+        multi_trie
+            .add(
+                "animals",
+                Trie::build(vec![String::from("CAT"), String::from("COW")]),
+            )
+            .add(
+                "common",
+                Trie::build(vec![String::from("CAT"), String::from("CAR")]),
+            );
+
+        assert_eq!(
+            vec![
+                (String::from("CAR"), vec!["common"]),
+                (String::from("CAT"), vec!["animals", "common"]),
+                (String::from("COW"), vec!["animals"]),
+            ],
+            multi_trie.words("C  ".chars())
+        );
+        assert!(multi_trie.words("DOG".chars()).is_empty());
+    }
+
+    #[test]
+    fn prefixes_works() {
+        let trie = Trie::build(vec![
+            String::from("CAT"),
+            String::from("BE"),
+            String::from("BEAT"),
+            String::from("ACT"),
+            String::from("A"),
+        ]);
+
+        assert_eq!(vec!["A", "AC", "B", "BE", "C", "CA"], trie.prefixes(2));
+        assert!(trie.prefixes(0).is_empty());
+        assert_eq!(
+            trie.prefixes(2),
+            trie.iter_prefixes(2).collect::<Vec<String>>()
+        );
+        assert_eq!(10, trie.iter_prefixes(10).count());
+    }
+
+    #[test]
+    fn all_words_works() {
+        let words = vec![
+            String::from("CAT"),
+            String::from("BE"),
+            String::from("BEAT"),
+            String::from("ACT"),
+            String::from("BEA"),
+            String::from("DOG"),
+        ];
+        let trie = Trie::build(words.clone());
+
+        let mut expected = words;
+        expected.sort();
+        assert_eq!(expected, trie.all_words());
+        assert_eq!(Some(String::from("ACT")), trie.iter_all_words().next());
+        assert!(Trie::build(vec![]).all_words().is_empty());
+    }
+
+    #[test]
+    fn diff_works() {
+        let old = Trie::build(vec![
+            String::from("BASS"),
+            String::from("BAT"),
+            String::from("BE"),
+            String::from("CAT"),
+        ]);
+        let new = Trie::build(vec![
+            String::from("BASS"),
+            String::from("BASSET"),
+            String::from("BE"),
+            String::from("DOG"),
+            String::from("CAT"),
+        ]);
+
+        let (only_old, only_new) = old.diff(&new);
+
+        assert_eq!(vec![String::from("BAT")], only_old);
+        assert_eq!(vec![String::from("BASSET"), String::from("DOG")], only_new);
+        assert_eq!((vec![], vec![]), old.diff(&old));
+    }
+
+    #[test]
+    fn original_form_works() {
+        let trie = Trie::build_preserving_case(vec![
+            String::from("Paris"),
+            String::from("NASA"),
+            String::from("iPod"),
+            String::from("pari"),
+        ]);
+
+        assert!(trie.is_viable("PARIS".chars()));
+        assert_eq!(Some(String::from("Paris")), trie.original_form("PARIS"));
+        assert_eq!(Some(String::from("Paris")), trie.original_form("paris"));
+        assert_eq!(Some(String::from("NASA")), trie.original_form("NASA"));
+        assert_eq!(Some(String::from("iPod")), trie.original_form("IPOD"));
+        assert_eq!(Some(String::from("pari")), trie.original_form("PARI"));
+        assert_eq!(None, trie.original_form("PAR"));
+        assert_eq!(None, trie.original_form("LONDON"));
+    }
 }
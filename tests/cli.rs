@@ -0,0 +1,41 @@
+#![cfg(feature = "cli")]
+
+use std::{env, fs, process::Command};
+
+use xwords::crossword::{Crossword, Direction};
+
+#[test]
+fn trace_lists_every_filled_slot() {
+    let trace_path = env::temp_dir().join(format!("xwords-trace-{}.json", std::process::id()));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xwords"))
+        .args(["--input", "grids/waffle.txt", "--trace"])
+        .arg(&trace_path)
+        .output()
+        .expect("Failed to run xwords");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let filled = Crossword::parse(String::from_utf8(output.stdout).unwrap()).unwrap();
+    let trace: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&trace_path).unwrap()).unwrap();
+    fs::remove_file(&trace_path).unwrap();
+
+    let entries = filled.numbered_entries();
+    assert_eq!(entries.len(), trace.len());
+    for entry in entries {
+        let direction = match entry.word_boundary.direction {
+            Direction::Across => "across",
+            Direction::Down => "down",
+        };
+        let steps: Vec<&serde_json::Value> = trace
+            .iter()
+            .filter(|step| step["number"] == entry.number && step["direction"] == direction)
+            .collect();
+        assert_eq!(1, steps.len(), "{} {}", entry.number, direction);
+        assert_eq!(entry.answer, steps[0]["word"]);
+    }
+}